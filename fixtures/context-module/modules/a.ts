@@ -0,0 +1 @@
+export default 'modules/a.ts';
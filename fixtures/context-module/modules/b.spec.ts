@@ -0,0 +1 @@
+export default 'modules/b.spec.ts';
@@ -0,0 +1 @@
+export default 'pages/about.tsx';
@@ -0,0 +1 @@
+export default 'pages/home.tsx';
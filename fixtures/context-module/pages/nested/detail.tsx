@@ -0,0 +1 @@
+export default 'pages/nested/detail.tsx';
@@ -0,0 +1,2 @@
+import './invalid-utf8';
+import './syntax-error';
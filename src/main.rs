@@ -16,6 +16,7 @@ use std::sync::{Arc, Mutex};
 use utils::path::join_paths;
 use utils::pretty::pretty_tree;
 use utils::resolver::simple_resolver;
+use utils::tree::CircularOptions;
 
 use parser::types::{IsModule, ParseOptions, Progress};
 
@@ -73,10 +74,14 @@ struct Args {
     #[arg(long)]
     tsconfig: Option<String>,
 
-    /// Transform typescript modules to javascript before analyze
+    /// Ignore type-only dependencies of typescript modules when analyze
     #[arg(short = 'T', long, default_value = "false")]
     transform: bool,
 
+    /// Parse js like files as Flow, Flow only syntax will be stripped before analyze
+    #[arg(long, default_value = "false")]
+    flow: bool,
+
     /// Exit with specified code
     #[arg(long)]
     exit_code: Option<String>,
@@ -91,6 +96,14 @@ struct Args {
     /// Skip parse import(...) statement
     #[arg(long, short='d')]
     skip_dynamic_imports: Option<String>,
+
+    /// Include jest.mock(...), vi.mock(...) and similar test mocks when detect circulars
+    #[arg(long, default_value = "false")]
+    include_test_mocks: bool,
+
+    /// Circular detection mode, eager only reports circulars evaluated when modules are loaded
+    #[arg(long, default_value = "all")]
+    circular_mode: String,
 }
 
 #[tokio::main]
@@ -105,7 +118,7 @@ async fn main() {
         std::process::exit(1);
     }
 
-    let exit_cases: HashSet<&str> = ["circular"].iter().cloned().collect();
+    let exit_cases: HashSet<&str> = ["circular", "parse-error"].iter().cloned().collect();
     let mut exit_codes: Vec<(String, i32)> = Vec::new();
 
     if let Some(exit_code_str) = &args.exit_code {
@@ -129,6 +142,11 @@ async fn main() {
         }
     }
 
+    if !["all", "eager"].contains(&args.circular_mode.as_str()) {
+        eprintln!("unsupported circular mode \"{}\"", args.circular_mode);
+        std::process::exit(1);
+    }
+
     let spinner = Arc::new(Mutex::new(Spinner::new(
         spinners::Dots,
         "Start analyzing dependencies...",
@@ -169,9 +187,10 @@ async fn main() {
         include: Regex::new(&args.include).unwrap_or_else(|_| Regex::new(".*").unwrap()),
         exclude: Regex::new(&args.exclude).unwrap_or_else(|_| Regex::new("$").unwrap()),
         tsconfig: args.tsconfig.clone(),
-        transform: args.transform,
         symbol: args.symbol,
         skip_dynamic_imports: args.skip_dynamic_imports.as_deref() == Some("tree"),
+        flow: args.flow,
+        experimental_decorators: false,
         is_module: IsModule::Unknown,
         progress: match no_progress {
             true => {
@@ -182,81 +201,86 @@ async fn main() {
         },
     };
 
-    let (dependency_tree, symbol_tree) = parse_dependency_tree(&files, &options).await;
+    let (dependency_tree, symbol_tree, module_tree, diagnostics) =
+        parse_dependency_tree(&files, &options).await;
 
     if utils::tree::is_empty(&dependency_tree) {
         println!("\nNo entry files were matched.");
         std::process::exit(1);
     }
 
-    let circulars: Vec<Vec<String>> = match args.no_circular {
-        false => utils::tree::parse_circular(
-            &mut dependency_tree.clone(),
-            args.skip_dynamic_imports.as_deref() == Some("circular"),
-        ),
-        true => vec![],
-    };
+    let entries_deep = futures::future::join_all(files.iter().map(|g: &String| {
+        let _g = g.clone();
+        async move {
+            glob(&_g)
+                .expect("Failed to read glob pattern")
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>()
+        }
+    }))
+    .await;
+    let entries: Vec<_> =
+        futures::future::join_all(entries_deep.into_iter().flatten().map(|name| {
+            let path_context: PathBuf = PathBuf::from(options.context.clone());
+            let _context: String = options.context.clone();
+            let _extensions: Vec<String> = options.extensions.clone();
+
+            let params_name: String = join_paths(&[&path_context, &name])
+                .to_string_lossy()
+                .into_owned();
+
+            let _clone_name: String = name.to_string_lossy().into_owned();
 
-    let output = args.output.clone();
-    if output.is_some() || !args.no_tree {
-        let entries_deep = futures::future::join_all(files.iter().map(|g: &String| {
-            let _g = g.clone();
             async move {
-                glob(&_g)
-                    .expect("Failed to read glob pattern")
-                    .filter_map(Result::ok)
-                    .collect::<Vec<_>>()
+                simple_resolver(&_context, &params_name, &_extensions, None)
+                    .await
+                    .map(|id| id.unwrap_or(_clone_name))
+                    // let it be shorten path
+                    .map(|id| utils::shorten::shorten_path(&id, &_context))
+                    .unwrap_or_else(|e| format!("Error: {}", e))
             }
         }))
-        .await;
-        let entries: Vec<_> =
-            futures::future::join_all(entries_deep.into_iter().flatten().map(|name| {
-                let path_context: PathBuf = PathBuf::from(options.context.clone());
-                let _context: String = options.context.clone();
-                let _extensions: Vec<String> = options.extensions.clone();
-
-                let params_name: String = join_paths(&[&path_context, &name])
-                    .to_string_lossy()
-                    .into_owned();
+        .await
+        .into_iter()
+        .collect();
 
-                let _clone_name: String = name.to_string_lossy().into_owned();
+    // -T 时忽略 type-only 的依赖，只保留从入口出发仍然可达的模块，输出的 JSON 也使用这棵树
+    let analyze_tree = match args.transform {
+        true => utils::tree::strip_type_only(&dependency_tree, &entries),
+        false => dependency_tree.clone(),
+    };
 
-                async move {
-                    simple_resolver(&_context, &params_name, &_extensions, None)
-                        .await
-                        .map(|id| id.unwrap_or(_clone_name))
-                        // let it be shorten path
-                        .map(|id| utils::shorten::shorten_path(&id, &_context))
-                        .unwrap_or_else(|e| format!("Error: {}", e))
-                }
-            }))
-            .await
-            .into_iter()
-            .collect();
+    let circular_options = CircularOptions {
+        skip_dynamic_imports: args.skip_dynamic_imports.as_deref() == Some("circular"),
+        include_test_mocks: args.include_test_mocks,
+        eager_only: args.circular_mode == "eager",
+    };
+    let circulars: Vec<Vec<String>> = match args.no_circular {
+        false => utils::tree::parse_circular(&mut analyze_tree.clone(), &circular_options),
+        true => vec![],
+    };
 
-        if output.is_some() {
-            let file = File::create(args.output.clone().unwrap()).expect("Failed to create file");
-            let mut data = json!({
-                "entries": entries,
-                "tree": dependency_tree,
-                "circulars": circulars,
-                "symbol": symbol_tree
-            });
-              if args.symbol {
-                data["symbol"] = json!(symbol_tree);
-            }
-            serde_json::to_writer_pretty(file, &data).expect("Failed to write JSON");
+    if let Some(output) = &args.output {
+        let file = File::create(output).expect("Failed to create file");
+        let mut data = json!({
+            "entries": entries,
+            "tree": analyze_tree,
+            "modules": module_tree,
+            "diagnostics": diagnostics,
+            "circulars": circulars,
+            "symbol": symbol_tree
+        });
+        if args.symbol {
+            data["symbol"] = json!(symbol_tree);
         }
+        serde_json::to_writer_pretty(file, &data).expect("Failed to write JSON");
+    }
 
-        if !args.no_tree {
-            println!("{}", "• Dependencies Tree".bold());
-            println!("{}", pretty_tree(&dependency_tree, &entries, ""));
-            println!("");
-        }
-        Some(entries)
-    } else {
-        None
-    };
+    if !args.no_tree {
+        println!("{}", "• Dependencies Tree".bold());
+        println!("{}", pretty_tree(&analyze_tree, &entries, ""));
+        println!("");
+    }
 
     let is_circular_empty = circulars.is_empty();
     if !args.no_circular {
@@ -269,7 +293,10 @@ async fn main() {
         if is_circular_empty {
             println!("🚀 No circular dependencies found.");
         } else {
-            println!("{}", utils::pretty::pretty_circular(&circulars, "  "));
+            println!(
+                "{}",
+                utils::pretty::pretty_circular(&circulars, &analyze_tree, &circular_options, "  ")
+            );
         }
     }
 
@@ -277,7 +304,10 @@ async fn main() {
         println!("\n{}", "• Warnings".bold().yellow());
         println!(
             "{}",
-            utils::pretty::pretty_warning(&utils::tree::parse_warnings(&dependency_tree), "  ")
+            utils::pretty::pretty_warning(
+                &utils::tree::parse_warnings(&analyze_tree, &module_tree, &diagnostics),
+                "  "
+            )
         );
     }
 
@@ -297,7 +327,7 @@ async fn main() {
             .collect();
         let unused_files: Vec<String> = short_all_files
             .iter()
-            .filter(|v| !dependency_tree.contains_key(*v))
+            .filter(|v| !analyze_tree.contains_key(*v))
             .cloned()
             .collect();
         println!("{}", "• Unused files".bold().cyan());
@@ -307,7 +337,7 @@ async fn main() {
               format!(
                   "  ✅ Congratulations, no unused file was found in your project. (total: {}, used: {})",
                   all_files.len(),
-                  dependency_tree.len()
+                  analyze_tree.len()
               )
               .bold()
               .green()
@@ -327,6 +357,9 @@ async fn main() {
                     std::process::exit(code);
                 }
             }
+            "parse-error" if !diagnostics.is_empty() => {
+                std::process::exit(code);
+            }
             _ => {}
         }
     }
@@ -1,7 +1,135 @@
+use crate::node_resolve::node_builtins::BUILTINS;
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 pub enum DependencyKind {
     CommonJS,
     StaticImport,
     DynamicImport,
     StaticExport,
+    RequireContext,      // webpack require.context(...)
+    ImportMetaGlob,      // vite import.meta.glob(...)，懒加载
+    ImportMetaGlobEager, // vite import.meta.glob(..., { eager: true })
+    DynamicContext,      // import(`./locales/${lang}.json`)，按静态前后缀展开
+    ImportEquals,        // import fs = require('fs')
+    ExportEquals,        // export = require('./foo')
+    TripleSlashPath,     // /// <reference path="./foo.d.ts" />
+    TripleSlashTypes,    // /// <reference types="node" />
+    HtmlScript,          // <script src="...">
+    HtmlModulePreload,   // <link rel="modulepreload" href="...">
+    HtmlStylesheet,      // <link rel="stylesheet" href="...">
+    CssImport,           // @import './reset.css'
+    CssUrl,              // url(./bg.png)
+    CssComposes,         // composes: base from './base.module.css'
+    SassUse,             // @use './variables'
+    SassForward,         // @forward './mixins'
+    Worker,              // new Worker(new URL('./worker.ts', import.meta.url))
+    ServiceWorker,       // navigator.serviceWorker.register('./sw.js')
+    ImportScripts,       // importScripts('./a.js')，classic worker 中同步加载
+    AssetUrl,            // new URL('./logo.svg', import.meta.url)
+    TestMock,            // jest.mock('../api')、vi.mock(...)、jest.requireActual(...)
+    CreateRequire,       // const require = createRequire(import.meta.url); require('./foo')
+    #[serde(rename = "AMD")]
+    Amd, // define(['./a'], factory)、require(['./a'], callback)
+}
+
+/// 模块格式，由后缀和最近的 package.json 中的 `type` 决定
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum ModuleFormat {
+    #[serde(rename = "ESM")]
+    Esm,
+    CommonJS,
+}
+
+/// 导出符号的种类，`Value` 表示无法确定具体声明的值，如 `export default expr` 或 CommonJS 导出
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum SymbolKind {
+    Value,
+    Type,      // interface、type alias 以及 export type
+    Enum,      // enum、const enum
+    Namespace, // namespace、module 以及 export * as ns
+    Class,
+    Function,
+    Variable,
+}
+
+/// 文件无法分析的原因
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum DiagnosticKind {
+    Read,   // 读取文件失败
+    Decode, // 不是 UTF-8 编码
+    Parse,  // swc 解析失败
+}
+
+/// 依赖树中节点的类型
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum NodeKind {
+    Script,
+    Html,
+    Stylesheet,
+    Asset,
+    Builtin,
+}
+
+impl NodeKind {
+    pub fn from_path(id: &str) -> NodeKind {
+        if BUILTINS.contains(&id) {
+            return NodeKind::Builtin;
+        }
+        let ext = std::path::Path::new(id)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "html" | "htm" => NodeKind::Html,
+            "css" | "scss" | "sass" | "less" => NodeKind::Stylesheet,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte"
+            | "astro" => NodeKind::Script,
+            _ => NodeKind::Asset,
+        }
+    }
+}
+
+impl DependencyKind {
+    /// 样式文件中的依赖，按样式的规则解析路径
+    pub fn is_style(&self) -> bool {
+        matches!(
+            self,
+            DependencyKind::CssImport
+                | DependencyKind::CssUrl
+                | DependencyKind::CssComposes
+                | DependencyKind::SassUse
+                | DependencyKind::SassForward
+        )
+    }
+
+    /// worker 运行在独立的上下文中，目标文件作为单独的入口分析，不参与调用方的循环依赖
+    pub fn is_worker(&self) -> bool {
+        matches!(self, DependencyKind::Worker | DependencyKind::ServiceWorker)
+    }
+
+    /// 测试框架中的 mock，只在测试中生效，默认不参与循环依赖检测
+    pub fn is_test_mock(&self) -> bool {
+        matches!(self, DependencyKind::TestMock)
+    }
+
+    /// 不会在模块求值时同步执行的依赖，如 `import()`、worker 和资源地址
+    pub fn is_lazy(&self) -> bool {
+        matches!(
+            self,
+            DependencyKind::DynamicImport
+                | DependencyKind::ImportMetaGlob
+                | DependencyKind::Worker
+                | DependencyKind::ServiceWorker
+                | DependencyKind::AssetUrl
+                | DependencyKind::TestMock
+        )
+    }
+
+    /// 是否为异步加载的依赖，受 `--skip-dynamic-imports` 控制
+    pub fn is_dynamic(&self) -> bool {
+        matches!(
+            self,
+            DependencyKind::DynamicImport | DependencyKind::ImportMetaGlob
+        )
+    }
 }
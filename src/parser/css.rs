@@ -0,0 +1,203 @@
+use super::consts::DependencyKind;
+use super::sfc::mask_outside;
+use crate::utils::path::join_paths;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref BLOCK_COMMENT_RE: Regex = Regex::new(r"(?s)/\*.*?\*/").unwrap();
+    // scss/less 的单行注释，只处理独占一行的，避免误伤 url(http://...)
+    static ref LINE_COMMENT_RE: Regex = Regex::new(r"(?m)^\s*//.*$").unwrap();
+    static ref IMPORT_RE: Regex = Regex::new(r"@import\s+([^;{}]+)").unwrap();
+    static ref USE_RE: Regex = Regex::new(r#"@(use|forward)\s+["']([^"']+)["']"#).unwrap();
+    static ref COMPOSES_RE: Regex =
+        Regex::new(r#"composes\s*:[^;}]*?\sfrom\s+["']([^"']+)["']"#).unwrap();
+    static ref URL_RE: Regex = Regex::new(r#"url\(\s*(?:"([^"]*)"|'([^']*)'|([^)\s]*))\s*\)"#).unwrap();
+    static ref STRING_RE: Regex = Regex::new(r#""([^"]*)"|'([^']*)'"#).unwrap();
+}
+
+pub fn is_stylesheet(id: &str) -> bool {
+    [".css", ".scss", ".sass", ".less"]
+        .iter()
+        .any(|ext| id.ends_with(ext))
+}
+
+fn first_match(captures: &Captures) -> String {
+    captures
+        .iter()
+        .skip(1)
+        .flatten()
+        .next()
+        .map_or(String::new(), |m| m.as_str().to_string())
+}
+
+/// 外部地址、data uri、锚点等不是文件依赖
+fn is_local_reference(request: &str) -> bool {
+    !(request.is_empty()
+        || request.starts_with('#')
+        || request.starts_with("//")
+        || request.starts_with("data:")
+        || request.contains("://")
+        || request.starts_with("sass:"))
+}
+
+/// 扫描样式文件中的 `@import`、`url()`、`composes: x from '...'` 以及 Sass 的 `@use`/`@forward`
+///
+/// 返回的位置是引用所在语句在原文件中的字节范围
+pub fn scan_stylesheet(content: &str) -> Vec<(String, DependencyKind, Range<usize>)> {
+    let content = BLOCK_COMMENT_RE.replace_all(content, |c: &Captures| mask_outside(&c[0], &[]));
+    let content = LINE_COMMENT_RE.replace_all(&content, |c: &Captures| mask_outside(&c[0], &[]));
+
+    let mut references: Vec<(String, DependencyKind, Range<usize>)> = vec![];
+
+    // @import 'a', 'b' screen; 或 @import url(a.css);
+    for captures in IMPORT_RE.captures_iter(&content) {
+        let range = captures.get(0).unwrap().range();
+        let statement = captures.get(1).unwrap().as_str();
+        for url in URL_RE.captures_iter(statement) {
+            references.push((first_match(&url), DependencyKind::CssImport, range.clone()));
+        }
+        if !URL_RE.is_match(statement) {
+            for string in STRING_RE.captures_iter(statement) {
+                references.push((
+                    first_match(&string),
+                    DependencyKind::CssImport,
+                    range.clone(),
+                ));
+            }
+        }
+    }
+    // @import 中的 url() 已经处理过，不再作为 CssUrl
+    let content = IMPORT_RE.replace_all(&content, |c: &Captures| mask_outside(&c[0], &[]));
+
+    for captures in USE_RE.captures_iter(&content) {
+        let kind = if &captures[1] == "use" {
+            DependencyKind::SassUse
+        } else {
+            DependencyKind::SassForward
+        };
+        references.push((
+            captures[2].to_string(),
+            kind,
+            captures.get(0).unwrap().range(),
+        ));
+    }
+
+    for captures in COMPOSES_RE.captures_iter(&content) {
+        references.push((
+            captures[1].to_string(),
+            DependencyKind::CssComposes,
+            captures.get(0).unwrap().range(),
+        ));
+    }
+
+    for captures in URL_RE.captures_iter(&content) {
+        references.push((
+            first_match(&captures),
+            DependencyKind::CssUrl,
+            captures.get(0).unwrap().range(),
+        ));
+    }
+
+    references.sort_by_key(|(_, _, range)| range.start);
+    references
+        .into_iter()
+        .filter(|(request, _, _)| is_local_reference(request))
+        .collect()
+}
+
+/// 计算样式依赖实际要解析的路径
+///
+/// CSS 中不带 `./` 的地址也是相对路径，Sass 还需要尝试 `_partial` 和 `_index`，
+/// 都找不到时按包名处理，webpack 风格的 `~` 前缀会被去掉。
+pub fn style_request_path(base_dir: &Path, request: &str) -> PathBuf {
+    let request = request.split(['?', '#']).next().unwrap_or(request);
+    if let Some(package) = request.strip_prefix('~') {
+        return PathBuf::from(package);
+    }
+
+    let path = join_paths(&[base_dir, Path::new(request)]);
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let mut candidates: Vec<PathBuf> = vec![path.clone(), dir.join(format!("_{}", file_name))];
+    for ext in ["scss", "sass", "less", "css"] {
+        candidates.push(dir.join(format!("{}.{}", file_name, ext)));
+        candidates.push(dir.join(format!("_{}.{}", file_name, ext)));
+    }
+    for ext in ["scss", "sass", "css"] {
+        candidates.push(path.join(format!("_index.{}", ext)));
+        candidates.push(path.join(format!("index.{}", ext)));
+    }
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .unwrap_or_else(|| PathBuf::from(request))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::fixture;
+
+    #[test]
+    fn test_scan_stylesheet() {
+        let content = r#"
+@use 'sass:math';
+@use "./variables" as vars;
+@forward 'mixins';
+@import url("./reset.css") screen;
+@import 'theme', "layout.css";
+/* @import './commented.css'; */
+// @import './line-commented.scss';
+.button {
+  composes: base from './base.module.css';
+  background: url(./images/bg.png?v=1) no-repeat, url(data:image/png;base64,AAAA);
+  mask: url('#mask');
+}
+"#;
+        let references = scan_stylesheet(content);
+        assert_eq!(&content[references[1].2.clone()], "@forward 'mixins'");
+        assert_eq!(
+            references
+                .into_iter()
+                .map(|(request, kind, _)| (request, kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("./variables".to_string(), DependencyKind::SassUse),
+                ("mixins".to_string(), DependencyKind::SassForward),
+                ("./reset.css".to_string(), DependencyKind::CssImport),
+                ("theme".to_string(), DependencyKind::CssImport),
+                ("layout.css".to_string(), DependencyKind::CssImport),
+                ("./base.module.css".to_string(), DependencyKind::CssComposes),
+                ("./images/bg.png?v=1".to_string(), DependencyKind::CssUrl),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_style_request_path() {
+        assert_eq!(
+            style_request_path(&fixture("css"), "variables"),
+            fixture("css/_variables.scss")
+        );
+        assert_eq!(
+            style_request_path(&fixture("css"), "./theme"),
+            fixture("css/theme/_index.scss")
+        );
+        assert_eq!(
+            style_request_path(&fixture("css"), "reset.css"),
+            fixture("css/reset.css")
+        );
+        assert_eq!(
+            style_request_path(&fixture("css"), "~normalize.css"),
+            PathBuf::from("normalize.css")
+        );
+    }
+}
@@ -1,19 +1,42 @@
 use crate::parser::types::{ExportSymbol, ImportSymbol};
 
-use super::consts::DependencyKind;
-use super::types::Dependency;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use super::consts::{DependencyKind, SymbolKind};
+use super::html::to_request;
+use super::types::{Dependency, Location};
+use crate::utils::context_module::{
+    expand_dynamic_context, expand_import_glob, expand_require_context, js_regex,
+};
+use crate::utils::path::{join_paths, relative_request};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use swc_core::common::comments::{Comments, SingleThreadedComments};
+use swc_core::common::{sync::Lrc, BytePos, SourceMap, SourceMapper, Span, Spanned, SyntaxContext};
 use swc_core::ecma::ast::{
-    Callee, Decl, ExportDecl, Ident, ImportSpecifier, ModuleExportName, Pat,
+    Callee, Decl, ExportDecl, Id, Ident, ImportSpecifier, ModuleExportName, Pat,
 };
 use swc_core::ecma::utils::swc_ecma_ast;
 use swc_core::ecma::visit::{Visit, VisitWith};
 
+lazy_static! {
+    static ref TRIPLE_SLASH_RE: Regex =
+        Regex::new(r#"^/\s*<reference\s+(path|types)\s*=\s*["']([^"']+)["']"#).unwrap();
+    // webpackChunkName: "name"、webpackPrefetch: true、@vite-ignore 等打包工具的 magic comment
+    static ref MAGIC_COMMENT_RE: Regex = Regex::new(
+        r#"(webpack\w+|@vite-ignore)(?:\s*:\s*("[^"]*"|'[^']*'|/(?:\\.|[^/])*/\w*|\[[^\]]*\]|[^,\s]+))?"#
+    )
+    .unwrap();
+    // dpdm-ignore、dpdm-ignore-next-line、dpdm-ignore-file，后面可以跟忽略的原因
+    static ref IGNORE_DIRECTIVE_RE: Regex =
+        Regex::new(r"^[\s*]*(dpdm-ignore(?:-next-line|-file)?)(?:\s+(.*?))?[\s*]*$").unwrap();
+}
+
 pub struct DependencyCollector {
     pub path: PathBuf,
     pub dependencies: Vec<Dependency>,
     pub id: String,
+    pub syntax_id: String, // 选择语法用的文件名，如 `<script lang="ts">` 的 `App.vue` 为 `App.vue.ts`
     pub skip_dynamic_imports: bool,
     pub collect_symbol: bool,
     pub imports: Vec<ImportSymbol>,
@@ -21,9 +44,59 @@ pub struct DependencyCollector {
     pub local_symbol_map: HashMap<String, Vec<String>>,
     pub next_import_id: usize, // pub local_dynamic_import_ids: Vec<usize>
     pub dynamic_import_expr_to_id_map: HashMap<String, usize>,
+    pub source_map: Lrc<SourceMap>,
+    pub comments: SingleThreadedComments,
+    pub value_refs: HashSet<String>, // 在值位置被引用的标识符，用于判断 type-only 导入
+    pub try_depth: usize,            // 当前所在 try 块的层数
+    pub conditional_depth: usize,    // 当前所在条件分支的层数
+    pub function_depth: usize,       // 当前所在不会在加载时执行的函数体的层数
+    pub immediate_function: bool,    // 下一个访问的函数是立即执行的
+    pub unresolved_ctxt: SyntaxContext, // resolver 中未绑定到任何声明的全局标识符的上下文
+    pub top_level_ctxt: SyntaxContext, // resolver 中顶层声明和 import 的上下文
+    pub string_consts: HashMap<Id, String>, // 字符串常量，如 `const BASE = './pages'`
+    pub require_bindings: HashMap<Id, DependencyKind>, // 局部的 require，如 createRequire() 创建的或 AMD factory 的参数
+    pub local_kinds: HashMap<String, SymbolKind>, // 顶层声明的种类，用于 `export { I }` 这样的本地导出
 }
 
 impl DependencyCollector {
+    /// 创建收集器，`unresolved_ctxt` 和 `top_level_ctxt` 来自对 program 运行的 resolver
+    ///
+    /// 默认不收集符号，也不跳过动态导入，`syntax_id` 和 `id` 相同
+    pub fn new(
+        id: String,
+        path: PathBuf,
+        source_map: Lrc<SourceMap>,
+        comments: SingleThreadedComments,
+        unresolved_ctxt: SyntaxContext,
+        top_level_ctxt: SyntaxContext,
+    ) -> DependencyCollector {
+        DependencyCollector {
+            path,
+            dependencies: vec![],
+            syntax_id: id.clone(),
+            id,
+            skip_dynamic_imports: false,
+            collect_symbol: false,
+            imports: vec![],
+            exports: vec![],
+            local_symbol_map: HashMap::new(),
+            next_import_id: 0,
+            dynamic_import_expr_to_id_map: HashMap::new(),
+            source_map,
+            comments,
+            value_refs: HashSet::new(),
+            try_depth: 0,
+            conditional_depth: 0,
+            function_depth: 0,
+            immediate_function: false,
+            unresolved_ctxt,
+            top_level_ctxt,
+            string_consts: HashMap::new(),
+            require_bindings: HashMap::new(),
+            local_kinds: HashMap::new(),
+        }
+    }
+
     fn collect_dynamic_import_ids(&mut self, expr: &swc_ecma_ast::Expr) -> Vec<usize> {
         struct ImportIdCollector<'a> {
             found_ids: Vec<usize>,
@@ -53,6 +126,9 @@ impl DependencyCollector {
                                     local: format!("__dynamic_import_{}", new_id),
                                     imported: "*".to_string(),
                                     source: request.clone(),
+                                    type_only: false,
+                                    source_id: None,
+                                    origin: None,
                                 };
                                 self.collector.imports.push(symbol);
                                 self.found_ids.push(new_id);
@@ -72,17 +148,787 @@ impl DependencyCollector {
         collector.found_ids
     }
 
-    fn add_import(&mut self, local: String, imported: String, source: String) {
+    pub fn add_dependency(
+        &mut self,
+        request: String,
+        kind: DependencyKind,
+        span: Span,
+    ) -> &mut Dependency {
+        let loc = Location::from_span(&self.source_map, span);
+        let eager = self.function_depth == 0 && !kind.is_lazy();
+        self.dependencies.push(Dependency {
+            issuer: self.path.to_string_lossy().to_string(),
+            request,
+            kind,
+            id: Some(self.id.clone()),
+            expression: None,
+            context_kind: None,
+            type_only: false,
+            in_try: self.try_depth > 0,
+            conditional: self.conditional_depth > 0,
+            in_function: self.function_depth > 0,
+            eager,
+            ignored: None,
+            metadata: BTreeMap::new(),
+            loc,
+        });
+        self.dependencies.last_mut().unwrap()
+    }
+
+    /// 访问函数体，立即执行的函数仍然在加载时运行，不计入 function_depth
+    fn visit_function_body(&mut self, visit: impl FnOnce(&mut Self)) {
+        let deferred = !std::mem::take(&mut self.immediate_function);
+        if deferred {
+            self.function_depth += 1;
+        }
+        visit(self);
+        if deferred {
+            self.function_depth -= 1;
+        }
+    }
+
+    /// 访问条件分支中的节点
+    fn visit_conditional(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.conditional_depth += 1;
+        visit(self);
+        self.conditional_depth -= 1;
+    }
+
+    fn is_typescript(&self) -> bool {
+        [".ts", ".tsx", ".mts", ".cts"]
+            .iter()
+            .any(|ext| self.syntax_id.ends_with(ext))
+    }
+
+    /// import 是否只在类型位置使用，和 TypeScript 的 import elision 一致
+    fn is_type_only_import(&self, import: &swc_ecma_ast::ImportDecl) -> bool {
+        if import.type_only {
+            return true;
+        }
+        if import.specifiers.is_empty() {
+            // import './side-effect'
+            return false;
+        }
+        let all_type_specifiers = import.specifiers.iter().all(
+            |specifier| matches!(specifier, ImportSpecifier::Named(named) if named.is_type_only),
+        );
+        if all_type_specifiers {
+            return true;
+        }
+        import.specifiers.iter().all(|specifier| {
+            let local = match specifier {
+                ImportSpecifier::Named(named) => &named.local,
+                ImportSpecifier::Default(default) => &default.local,
+                ImportSpecifier::Namespace(ns) => &ns.local,
+            };
+            self.is_type_only_binding(local)
+        })
+    }
+
+    /// TypeScript 中只在类型位置引用的导入绑定
+    fn is_type_only_binding(&self, local: &Ident) -> bool {
+        self.is_typescript() && !self.value_refs.contains(&*local.sym)
+    }
+
+    /// 处理参数不是字符串字面量的 import()/require()，如 `import(`./locales/${lang}.json`)`
+    ///
+    /// 能确定目录的按 webpack 的方式展开为 DynamicContext 依赖，否则记录为无法分析的导入
+    fn add_context_dependencies(
+        &mut self,
+        expr: &swc_ecma_ast::Expr,
+        kind: DependencyKind,
+        span: Span,
+    ) {
+        let mut parts = vec![];
+        self.split_request_parts(expr, &mut parts);
+
+        let mut segments: Vec<String> = vec![String::new()];
+        for part in parts {
+            match part {
+                Some(s) => segments.last_mut().unwrap().push_str(&s),
+                None => segments.push(String::new()),
+            }
+        }
+        segments[0] = self.relative_to_issuer(&segments[0]);
+
+        let expression = self
+            .source_map
+            .span_to_snippet(expr.span())
+            .unwrap_or_else(|_| segments.join("*"));
+        // 没有动态部分，如 import(`./a.js`) 或 require(path.join(__dirname, 'a'))，等价于字符串字面量
+        if segments.len() == 1 {
+            self.add_dependency(segments.remove(0), kind, span)
+                .expression = Some(expression);
+            return;
+        }
+
+        match expand_dynamic_context(&self.base_dir(), &segments, Path::new(&self.id)) {
+            Some(requests) if !requests.is_empty() => {
+                for request in requests {
+                    let dependency =
+                        self.add_dependency(request, DependencyKind::DynamicContext, span);
+                    dependency.expression = Some(expression.clone());
+                    dependency.context_kind = Some(kind.clone());
+                    // import(`./locales/${lang}.json`) 是异步加载的
+                    dependency.eager &= !kind.is_lazy();
+                }
+            }
+            // 目录不存在或没有匹配的文件，以 `./pages/*.js` 的形式记录为找不到的依赖
+            Some(_) => {
+                let dependency =
+                    self.add_dependency(segments.join("*"), DependencyKind::DynamicContext, span);
+                dependency.expression = Some(expression);
+                dependency.context_kind = Some(kind);
+            }
+            None => {
+                let dependency =
+                    self.add_dependency(expression.clone(), DependencyKind::DynamicContext, span);
+                dependency.id = None;
+                dependency.expression = Some(expression);
+                dependency.context_kind = Some(kind);
+            }
+        }
+    }
+
+    /// 是否为全局的 `require`，局部变量或参数中同名的 require 不是 CommonJS 导入
+    fn is_global_require(&self, ident: &Ident) -> bool {
+        ident.sym == *"require" && ident.ctxt == self.unresolved_ctxt
+    }
+
+    /// callee 对应的 require 类型，`createRequire()` 创建的 require 和 Node 的 require 语义一致，
+    /// 但可以在 ESM 中使用，AMD factory 的 require 参数按 AMD 处理
+    fn require_kind(&self, callee: &swc_ecma_ast::Expr) -> Option<DependencyKind> {
+        match callee {
+            swc_ecma_ast::Expr::Ident(ident) if self.is_global_require(ident) => {
+                Some(DependencyKind::CommonJS)
+            }
+            swc_ecma_ast::Expr::Ident(ident) => self.require_bindings.get(&ident.to_id()).cloned(),
+            // createRequire(import.meta.url)('./foo')
+            swc_ecma_ast::Expr::Call(call) if is_create_require_call(call) => {
+                Some(DependencyKind::CreateRequire)
+            }
+            swc_ecma_ast::Expr::Paren(paren) => self.require_kind(&paren.expr),
+            _ => None,
+        }
+    }
+
+    /// AMD 的 `define([...], factory)`、`define(function (require) {...})` 和 `require([...], callback)`
+    ///
+    /// 不是 AMD 调用时返回 false
+    fn visit_amd_call(
+        &mut self,
+        expr: &swc_ecma_ast::CallExpr,
+        callee: &swc_ecma_ast::Expr,
+    ) -> bool {
+        let is_define = matches!(
+            callee,
+            swc_ecma_ast::Expr::Ident(ident) if ident.sym == *"define" && ident.ctxt == self.unresolved_ctxt
+        );
+        // define('name', [...], factory) 的第一个参数是模块名
+        let args: Vec<&swc_ecma_ast::ExprOrSpread> = expr
+            .args
+            .iter()
+            .skip_while(|arg| {
+                is_define
+                    && matches!(
+                        *arg.expr,
+                        swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(_))
+                    )
+            })
+            .collect();
+        let deps = match args.first().map(|arg| &*arg.expr) {
+            Some(swc_ecma_ast::Expr::Array(array)) => Some(array),
+            _ => None,
+        };
+        if !is_define && (deps.is_none() || self.require_kind(callee).is_none()) {
+            return false;
+        }
+
+        for elem in deps.iter().flat_map(|array| array.elems.iter().flatten()) {
+            if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) = *elem.expr {
+                if let Some(request) = amd_request(&s.value) {
+                    let dependency =
+                        self.add_dependency(request, DependencyKind::Amd, elem.expr.span());
+                    // require([...], callback) 是异步加载的
+                    dependency.eager &= is_define;
+                }
+            }
+        }
+
+        for arg in args {
+            // factory 的 require 参数按 CommonJS sugar 处理，其中的 require('./a') 也是 AMD 依赖
+            if is_define {
+                for param in function_params(&arg.expr) {
+                    if let Pat::Ident(binding) = param {
+                        if binding.id.sym == *"require" {
+                            self.require_bindings
+                                .insert(binding.id.to_id(), DependencyKind::Amd);
+                        }
+                    }
+                }
+            }
+            // define 的 factory 在依赖加载完成后执行，等同于模块的顶层代码
+            self.immediate_function = is_define && is_function(&arg.expr);
+            arg.visit_with(self);
+        }
+        true
+    }
+
+    /// 将 `'./a/' + b` 或 `./a/${b}` 拆成静态片段，动态部分记为 None
+    fn split_request_parts(&self, expr: &swc_ecma_ast::Expr, parts: &mut Vec<Option<String>>) {
+        match expr {
+            swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(s)) => {
+                parts.push(Some(s.value.to_string()))
+            }
+            swc_ecma_ast::Expr::Tpl(tpl) => {
+                for (i, quasi) in tpl.quasis.iter().enumerate() {
+                    let text = match &quasi.cooked {
+                        Some(cooked) => cooked.to_string(),
+                        None => quasi.raw.to_string(),
+                    };
+                    parts.push(Some(text));
+                    if let Some(expr) = tpl.exprs.get(i) {
+                        self.split_request_parts(expr, parts);
+                    }
+                }
+            }
+            swc_ecma_ast::Expr::Bin(bin) if bin.op == swc_ecma_ast::BinaryOp::Add => {
+                self.split_request_parts(&bin.left, parts);
+                self.split_request_parts(&bin.right, parts);
+            }
+            swc_ecma_ast::Expr::Paren(paren) => self.split_request_parts(&paren.expr, parts),
+            expr => parts.push(self.eval_value(expr)),
+        }
+    }
+
+    /// 计算表达式的字符串值，包含动态部分时返回 None
+    fn eval_string(&self, expr: &swc_ecma_ast::Expr) -> Option<String> {
+        let mut parts = vec![];
+        self.split_request_parts(expr, &mut parts);
+        parts.into_iter().collect()
+    }
+
+    /// 计算字符串拼接之外的常量：`__dirname`、`__filename`、字符串常量以及
+    /// `path.join`/`path.resolve`
+    fn eval_value(&self, expr: &swc_ecma_ast::Expr) -> Option<String> {
+        match expr {
+            swc_ecma_ast::Expr::Ident(ident) if ident.ctxt == self.unresolved_ctxt => {
+                match &*ident.sym {
+                    "__dirname" => Some(self.absolute_base_dir()),
+                    "__filename" => Some(join_paths(&[self.absolute_base_dir(), self.file_name()])),
+                    _ => None,
+                }
+                .map(|path| path.to_string_lossy().to_string())
+            }
+            swc_ecma_ast::Expr::Ident(ident) => self.string_consts.get(&ident.to_id()).cloned(),
+            swc_ecma_ast::Expr::Call(call) => {
+                let Callee::Expr(ref callee) = call.callee else {
+                    return None;
+                };
+                let swc_ecma_ast::Expr::Member(ref member) = **callee else {
+                    return None;
+                };
+                let (swc_ecma_ast::Expr::Ident(obj), swc_ecma_ast::MemberProp::Ident(prop)) =
+                    (&*member.obj, &member.prop)
+                else {
+                    return None;
+                };
+                if obj.sym != *"path" {
+                    return None;
+                }
+                let args = call
+                    .args
+                    .iter()
+                    .map(|arg| match arg.spread {
+                        Some(_) => None,
+                        None => self.eval_string(&arg.expr),
+                    })
+                    .collect::<Option<Vec<String>>>()?;
+                let path = join_paths(&args);
+                match &*prop.sym {
+                    "join" => Some(path),
+                    // path.resolve 的相对路径基于运行时的 cwd，无法确定
+                    "resolve" if path.is_absolute() => Some(path),
+                    _ => None,
+                }
+                .map(|path| path.to_string_lossy().to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// `__dirname` 计算出的绝对路径转换为相对于当前文件的请求，保留末尾的 `/`
+    fn relative_to_issuer(&self, request: &str) -> String {
+        if !Path::new(request).is_absolute() {
+            return request.to_string();
+        }
+        let mut relative = relative_request(self.absolute_base_dir(), request);
+        if request.ends_with('/') {
+            relative.push('/');
+        }
+        relative
+    }
+
+    /// 当前文件所在目录的绝对路径，即 `__dirname`
+    fn absolute_base_dir(&self) -> PathBuf {
+        std::path::absolute(self.base_dir()).unwrap_or_else(|_| self.base_dir())
+    }
+
+    fn file_name(&self) -> PathBuf {
+        Path::new(&self.id)
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or_default()
+    }
+
+    /// 当前文件所在目录，require.context / import.meta.glob 相对它展开
+    fn base_dir(&self) -> PathBuf {
+        Path::new(&self.id)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    }
+
+    /// require.context(directory, useSubdirectories = true, regExp = /^\.\/.*$/)
+    fn visit_require_context(&mut self, expr: &swc_ecma_ast::CallExpr) {
+        let directory = match expr.args.first().map(|arg| &*arg.expr) {
+            Some(swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(s))) => s.value.to_string(),
+            _ => return,
+        };
+        let recursive = match expr.args.get(1).map(|arg| &*arg.expr) {
+            Some(swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Bool(b))) => b.value,
+            _ => true,
+        };
+        let regexp = match expr.args.get(2).map(|arg| &*arg.expr) {
+            Some(swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Regex(re))) => {
+                js_regex(&re.exp, &re.flags)
+            }
+            None => js_regex(r"^\./.*$", ""),
+            _ => None,
+        };
+        let Some(regexp) = regexp else {
+            return;
+        };
+
+        for request in expand_require_context(&self.base_dir(), &directory, recursive, &regexp) {
+            self.add_dependency(request, DependencyKind::RequireContext, expr.span);
+        }
+    }
+
+    /// import.meta.glob(patterns, { eager })，eager 的 glob 等价于静态导入
+    fn visit_import_meta_glob(&mut self, expr: &swc_ecma_ast::CallExpr, eager: bool) {
+        let patterns: Vec<String> = match expr.args.first().map(|arg| &*arg.expr) {
+            Some(swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(s))) => vec![s.value.to_string()],
+            Some(swc_ecma_ast::Expr::Array(arr)) => arr
+                .elems
+                .iter()
+                .flatten()
+                .filter_map(|elem| match &*elem.expr {
+                    swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(s)) => Some(s.value.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            _ => return,
+        };
+
+        let eager = eager
+            || expr
+                .args
+                .get(1)
+                .is_some_and(|arg| is_eager_option(&arg.expr));
+        let kind = if eager {
+            DependencyKind::ImportMetaGlobEager
+        } else {
+            DependencyKind::ImportMetaGlob
+        };
+        if kind.is_dynamic() && self.skip_dynamic_imports {
+            return;
+        }
+
+        for request in expand_import_glob(&self.base_dir(), &patterns) {
+            self.add_dependency(request, kind.clone(), expr.span);
+        }
+    }
+
+    /// 收集文件开头的三斜线指令，只有出现在第一条语句之前的注释才生效
+    fn collect_triple_slash_directives(&mut self, first_item: Option<BytePos>) {
+        let mut directives: Vec<(BytePos, Span, String, String)> = vec![];
+        {
+            let (leading, _) = self.comments.borrow_all();
+            for comments in leading.values() {
+                for comment in comments {
+                    if first_item.is_some_and(|pos| comment.span.lo >= pos) {
+                        continue;
+                    }
+                    if let Some(captures) = TRIPLE_SLASH_RE.captures(&comment.text) {
+                        directives.push((
+                            comment.span.lo,
+                            comment.span,
+                            captures[1].to_string(),
+                            captures[2].to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+        directives.sort();
+
+        for (_, span, name, value) in directives {
+            if name == "path" {
+                // path 总是相对于当前文件
+                let request = if value.starts_with('.') || value.starts_with('/') {
+                    value
+                } else {
+                    format!("./{}", value)
+                };
+                self.add_dependency(request, DependencyKind::TripleSlashPath, span)
+                    .type_only = true;
+            } else {
+                self.add_dependency(value, DependencyKind::TripleSlashTypes, span)
+                    .type_only = true;
+            }
+        }
+    }
+
+    /// 记录顶层声明的种类，声明可能出现在 `export { I }` 之后，所以在遍历前收集
+    ///
+    /// 同名的类型和值（如 `interface A` 和 `const A`）按值处理
+    fn collect_local_kinds(&mut self, program: &swc_ecma_ast::Program) {
+        let decls: Vec<&Decl> = match program {
+            swc_ecma_ast::Program::Module(module) => module
+                .body
+                .iter()
+                .filter_map(|item| match item {
+                    swc_ecma_ast::ModuleItem::Stmt(swc_ecma_ast::Stmt::Decl(decl)) => Some(decl),
+                    swc_ecma_ast::ModuleItem::ModuleDecl(swc_ecma_ast::ModuleDecl::ExportDecl(
+                        export,
+                    )) => Some(&export.decl),
+                    _ => None,
+                })
+                .collect(),
+            swc_ecma_ast::Program::Script(script) => script
+                .body
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    swc_ecma_ast::Stmt::Decl(decl) => Some(decl),
+                    _ => None,
+                })
+                .collect(),
+        };
+
+        for decl in decls {
+            let names: Vec<(String, SymbolKind)> = match decl {
+                Decl::Class(class) => vec![(class.ident.sym.to_string(), SymbolKind::Class)],
+                Decl::Fn(func) => vec![(func.ident.sym.to_string(), SymbolKind::Function)],
+                Decl::Var(var) => var
+                    .decls
+                    .iter()
+                    .flat_map(|decl| pat_bindings(&decl.name, self.top_level_ctxt))
+                    .map(|binding| (binding.local, SymbolKind::Variable))
+                    .collect(),
+                Decl::Using(using) => using
+                    .decls
+                    .iter()
+                    .flat_map(|decl| pat_bindings(&decl.name, self.top_level_ctxt))
+                    .map(|binding| (binding.local, SymbolKind::Variable))
+                    .collect(),
+                Decl::TsInterface(interface) => {
+                    vec![(interface.id.sym.to_string(), SymbolKind::Type)]
+                }
+                Decl::TsTypeAlias(alias) => vec![(alias.id.sym.to_string(), SymbolKind::Type)],
+                Decl::TsEnum(ts_enum) => vec![(ts_enum.id.sym.to_string(), SymbolKind::Enum)],
+                Decl::TsModule(module) => match &module.id {
+                    swc_ecma_ast::TsModuleName::Ident(ident) => {
+                        vec![(ident.sym.to_string(), SymbolKind::Namespace)]
+                    }
+                    swc_ecma_ast::TsModuleName::Str(_) => vec![],
+                },
+            };
+            for (local, kind) in names {
+                match self.local_kinds.get(&local) {
+                    Some(existing) if *existing != SymbolKind::Type => {}
+                    _ => {
+                        self.local_kinds.insert(local, kind);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 读取参数前的 magic comment，如 `import(/* webpackChunkName: "a" */ './a')`
+    fn magic_comments(&self, pos: BytePos) -> BTreeMap<String, String> {
+        let mut metadata = BTreeMap::new();
+        for comment in self.comments.get_leading(pos).unwrap_or_default() {
+            for captures in MAGIC_COMMENT_RE.captures_iter(&comment.text) {
+                let value = captures.get(2).map_or("true", |value| value.as_str());
+                let value = value
+                    .strip_prefix(['"', '\''])
+                    .and_then(|value| value.strip_suffix(['"', '\'']))
+                    .unwrap_or(value);
+                metadata.insert(captures[1].to_string(), value.to_string());
+            }
+        }
+        metadata
+    }
+
+    /// 根据注释指令标记被忽略的依赖
+    ///
+    /// - `// dpdm-ignore-file [reason]` 忽略当前文件的所有依赖
+    /// - `// dpdm-ignore-next-line [reason]` 忽略下一行的依赖
+    /// - `/* dpdm-ignore [reason] */` 忽略同一行的依赖
+    fn apply_ignore_directives(&mut self) {
+        let mut file_reason: Option<String> = None;
+        let mut line_reasons: HashMap<usize, String> = HashMap::new();
+        {
+            let (leading, trailing) = self.comments.borrow_all();
+            for comment in leading.values().chain(trailing.values()).flatten() {
+                let Some(captures) = IGNORE_DIRECTIVE_RE.captures(&comment.text) else {
+                    continue;
+                };
+                let directive = &captures[1];
+                let reason = captures
+                    .get(2)
+                    .map(|reason| reason.as_str().to_string())
+                    .filter(|reason| !reason.is_empty())
+                    .unwrap_or_else(|| directive.to_string());
+                let line = self.source_map.lookup_char_pos(comment.span.hi).line;
+                match directive {
+                    "dpdm-ignore-file" => file_reason = Some(reason),
+                    "dpdm-ignore-next-line" => {
+                        line_reasons.insert(line + 1, reason);
+                    }
+                    _ => {
+                        line_reasons.insert(line, reason);
+                    }
+                }
+            }
+        }
+
+        for dependency in &mut self.dependencies {
+            let line_reason = dependency.loc.as_ref().and_then(|loc| {
+                // 多行的 import 可以在任意一行上写忽略注释
+                (loc.start_line..=loc.end_line).find_map(|line| line_reasons.get(&line))
+            });
+            if let Some(reason) = line_reason.or(file_reason.as_ref()) {
+                dependency.ignored = Some(reason.clone());
+            }
+        }
+    }
+
+    fn add_import(&mut self, local: String, imported: String, source: String, type_only: bool) {
         let symbol = ImportSymbol {
             id: self.next_import_id,
             local,
             imported,
             source,
+            type_only,
+            source_id: None,
+            origin: None,
         };
         self.next_import_id += 1;
         self.imports.push(symbol);
     }
 
+    /// `require('./foo')` 返回 `('./foo', "*")`，`require('./foo').bar` 返回 `('./foo', "bar")`
+    fn require_request(&self, expr: &swc_ecma_ast::Expr) -> Option<(String, String)> {
+        match expr {
+            swc_ecma_ast::Expr::Call(call) => {
+                let Callee::Expr(ref callee) = call.callee else {
+                    return None;
+                };
+                self.require_kind(callee)?;
+                let request = self.eval_string(&call.args.first()?.expr)?;
+                Some((self.relative_to_issuer(&request), "*".to_string()))
+            }
+            swc_ecma_ast::Expr::Member(member) => {
+                let imported = member_prop_name(&member.prop)?;
+                let (request, _) = self.require_request(&member.obj)?;
+                Some((request, imported))
+            }
+            swc_ecma_ast::Expr::Paren(paren) => self.require_request(&paren.expr),
+            _ => None,
+        }
+    }
+
+    /// 是否为 CommonJS 的导出对象 `exports` 或 `module.exports`
+    fn is_cjs_exports(&self, expr: &swc_ecma_ast::Expr) -> bool {
+        match expr {
+            swc_ecma_ast::Expr::Ident(ident) => {
+                ident.sym == *"exports" && ident.ctxt == self.unresolved_ctxt
+            }
+            swc_ecma_ast::Expr::Member(member) => self.is_module_exports(member),
+            _ => false,
+        }
+    }
+
+    fn is_module_exports(&self, member: &swc_ecma_ast::MemberExpr) -> bool {
+        matches!(
+            &*member.obj,
+            swc_ecma_ast::Expr::Ident(obj) if obj.sym == *"module" && obj.ctxt == self.unresolved_ctxt
+        ) && member_prop_name(&member.prop).as_deref() == Some("exports")
+    }
+
+    /// 记录一个本地导出，`node` 中引用的 import 作为它的依赖，返回引用的标识符
+    fn add_local_export<N>(
+        &mut self,
+        node: &N,
+        local: String,
+        exported: String,
+        kind: SymbolKind,
+    ) -> Vec<String>
+    where
+        N: for<'a> VisitWith<IdentCollector<'a>>,
+    {
+        let mut used_idents = vec![];
+        node.visit_with(&mut IdentCollector {
+            idents: &mut used_idents,
+            top_level_ctxt: self.top_level_ctxt,
+        });
+        let depends_on = self.find_unique_import_ids_recursive(&used_idents);
+        self.exports.push(ExportSymbol {
+            local,
+            exported,
+            reexport_source: None,
+            reexport_id: None,
+            depends_on,
+            kind,
+        });
+        used_idents
+    }
+
+    /// 记录一个 CommonJS 导出，`exports.foo = require('./foo').bar` 视为 re-export
+    fn add_cjs_export(&mut self, exported: String, value: &swc_ecma_ast::Expr) {
+        if let Some((request, imported)) = self.require_request(value) {
+            self.exports.push(ExportSymbol {
+                local: imported,
+                exported,
+                reexport_source: Some(request),
+                reexport_id: None,
+                depends_on: vec![],
+                kind: SymbolKind::Value,
+            });
+            return;
+        }
+        let local = match value {
+            swc_ecma_ast::Expr::Ident(ident) => ident.sym.to_string(),
+            _ => exported.clone(),
+        };
+        self.add_local_export(value, local, exported, SymbolKind::Value);
+    }
+
+    /// `module.exports = { a, b: c }` 按属性导出，其余的 `module.exports = x` 作为 default 导出
+    fn add_cjs_module_exports(&mut self, value: &swc_ecma_ast::Expr) {
+        let swc_ecma_ast::Expr::Object(obj) = value else {
+            return self.add_cjs_export("default".to_string(), value);
+        };
+        for prop in &obj.props {
+            match prop {
+                swc_ecma_ast::PropOrSpread::Spread(spread) => {
+                    // ...require('./foo')
+                    if let Some((request, _)) = self.require_request(&spread.expr) {
+                        self.exports.push(ExportSymbol {
+                            local: "*".to_string(),
+                            exported: "*".to_string(),
+                            reexport_source: Some(request),
+                            reexport_id: None,
+                            depends_on: vec![],
+                            kind: SymbolKind::Value,
+                        });
+                    }
+                }
+                swc_ecma_ast::PropOrSpread::Prop(prop) => match &**prop {
+                    swc_ecma_ast::Prop::Shorthand(ident) => self.add_cjs_export(
+                        ident.sym.to_string(),
+                        &swc_ecma_ast::Expr::Ident(ident.clone()),
+                    ),
+                    swc_ecma_ast::Prop::KeyValue(kv) => {
+                        if let Some(key) = prop_name(&kv.key) {
+                            self.add_cjs_export(key, &kv.value);
+                        }
+                    }
+                    prop => {
+                        let key = match prop {
+                            swc_ecma_ast::Prop::Method(method) => prop_name(&method.key),
+                            swc_ecma_ast::Prop::Getter(getter) => prop_name(&getter.key),
+                            _ => None,
+                        };
+                        if let Some(key) = key {
+                            self.add_local_export(prop, key.clone(), key, SymbolKind::Value);
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// `Object.defineProperty(exports, 'foo', {...})` 以及 TypeScript 的 `__exportStar(require('./foo'), exports)`
+    fn collect_cjs_export_call(&mut self, expr: &swc_ecma_ast::CallExpr) {
+        let Callee::Expr(ref callee) = expr.callee else {
+            return;
+        };
+        let callee_name = match &**callee {
+            swc_ecma_ast::Expr::Ident(ident) => Some(ident.sym.to_string()),
+            swc_ecma_ast::Expr::Member(member) => match &*member.obj {
+                swc_ecma_ast::Expr::Ident(obj) if obj.sym == *"Object" => {
+                    member_prop_name(&member.prop).map(|prop| format!("Object.{}", prop))
+                }
+                // tslib_1.__exportStar(...)
+                _ => member_prop_name(&member.prop),
+            },
+            _ => None,
+        };
+        match callee_name.as_deref() {
+            Some("Object.defineProperty") => {
+                let [target, name, descriptor, ..] = &expr.args[..] else {
+                    return;
+                };
+                if !self.is_cjs_exports(&target.expr) {
+                    return;
+                }
+                let Some(exported) = self.eval_string(&name.expr) else {
+                    return;
+                };
+                if exported == "__esModule" {
+                    return;
+                }
+                self.add_local_export(
+                    &descriptor.expr,
+                    exported.clone(),
+                    exported,
+                    SymbolKind::Value,
+                );
+            }
+            Some("__exportStar" | "__export") => {
+                let Some(arg) = expr.args.first() else {
+                    return;
+                };
+                // __exportStar(require('./foo'), exports) 或 __exportStar(foo_1, exports)
+                let request = match self.require_request(&arg.expr) {
+                    Some((request, _)) => Some(request),
+                    None => match &*arg.expr {
+                        swc_ecma_ast::Expr::Ident(ident) => self
+                            .imports
+                            .iter()
+                            .find(|import| import.local == *ident.sym)
+                            .map(|import| import.source.clone()),
+                        _ => None,
+                    },
+                };
+                if let Some(request) = request {
+                    self.exports.push(ExportSymbol {
+                        local: "*".to_string(),
+                        exported: "*".to_string(),
+                        reexport_source: Some(request),
+                        reexport_id: None,
+                        depends_on: vec![],
+                        kind: SymbolKind::Value,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// 匹配某个本地符号名对应的 import id（可多个）
     fn find_import_ids(&self, local: &str) -> Vec<usize> {
         self.imports
@@ -122,27 +968,464 @@ impl DependencyCollector {
     }
 }
 
+/// 解构模式中绑定的一个名称
+pub struct PatBinding {
+    pub local: String,
+    /// 最外层对象解构的属性名，如 `const { a: { b } } = x` 中 b 的 key 为 a，数组和 rest 为 None
+    pub key: Option<String>,
+    /// 默认值中引用的标识符
+    pub used_idents: Vec<String>,
+    /// 是否为模块顶层的绑定，函数和块中的同名绑定不会遮蔽顶层的符号
+    pub top_level: bool,
+}
+
+/// 展开解构模式（嵌套、默认值、rest），每个绑定的名称单独记录
+pub fn pat_bindings(pat: &Pat, top_level_ctxt: SyntaxContext) -> Vec<PatBinding> {
+    fn walk(
+        pat: &Pat,
+        key: Option<String>,
+        top_level_ctxt: SyntaxContext,
+        bindings: &mut Vec<PatBinding>,
+    ) {
+        match pat {
+            Pat::Ident(binding) => bindings.push(PatBinding {
+                local: binding.id.sym.to_string(),
+                key,
+                used_idents: vec![],
+                top_level: binding.id.ctxt == top_level_ctxt,
+            }),
+            Pat::Array(array) => {
+                for elem in array.elems.iter().flatten() {
+                    walk(elem, key.clone(), top_level_ctxt, bindings);
+                }
+            }
+            Pat::Rest(rest) => walk(&rest.arg, key, top_level_ctxt, bindings),
+            Pat::Object(obj) => {
+                for prop in &obj.props {
+                    match prop {
+                        swc_ecma_ast::ObjectPatProp::KeyValue(kv) => {
+                            let key = key.clone().or_else(|| prop_name(&kv.key));
+                            walk(&kv.value, key, top_level_ctxt, bindings);
+                        }
+                        swc_ecma_ast::ObjectPatProp::Assign(assign) => {
+                            let mut used_idents = vec![];
+                            if let Some(value) = &assign.value {
+                                value.visit_with(&mut IdentCollector {
+                                    idents: &mut used_idents,
+                                    top_level_ctxt,
+                                });
+                            }
+                            bindings.push(PatBinding {
+                                local: assign.key.sym.to_string(),
+                                key: key.clone().or_else(|| Some(assign.key.sym.to_string())),
+                                used_idents,
+                                top_level: assign.key.ctxt == top_level_ctxt,
+                            });
+                        }
+                        swc_ecma_ast::ObjectPatProp::Rest(rest) => {
+                            walk(&rest.arg, key.clone(), top_level_ctxt, bindings)
+                        }
+                    }
+                }
+            }
+            Pat::Assign(assign) => {
+                let start = bindings.len();
+                walk(&assign.left, key, top_level_ctxt, bindings);
+                let mut used_idents = vec![];
+                assign.right.visit_with(&mut IdentCollector {
+                    idents: &mut used_idents,
+                    top_level_ctxt,
+                });
+                for binding in &mut bindings[start..] {
+                    binding.used_idents.extend(used_idents.iter().cloned());
+                }
+            }
+            Pat::Expr(_) | Pat::Invalid(_) => {}
+        }
+    }
+
+    let mut bindings = vec![];
+    walk(pat, None, top_level_ctxt, &mut bindings);
+    bindings
+}
+
+/// `obj.foo` 或 `obj['foo']` 中的属性名
+fn member_prop_name(prop: &swc_ecma_ast::MemberProp) -> Option<String> {
+    match prop {
+        swc_ecma_ast::MemberProp::Ident(ident) => Some(ident.sym.to_string()),
+        swc_ecma_ast::MemberProp::Computed(computed) => match &*computed.expr {
+            swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(s)) => Some(s.value.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 对象字面量中的属性名，计算属性返回 None
+fn prop_name(key: &swc_ecma_ast::PropName) -> Option<String> {
+    match key {
+        swc_ecma_ast::PropName::Ident(ident) => Some(ident.sym.to_string()),
+        swc_ecma_ast::PropName::Str(s) => Some(s.value.to_string()),
+        _ => None,
+    }
+}
+
+/// `new URL('./a.js', import.meta.url)` 中引用的地址
+fn import_meta_url_request(new: &swc_ecma_ast::NewExpr) -> Option<String> {
+    let swc_ecma_ast::Expr::Ident(ref callee) = *new.callee else {
+        return None;
+    };
+    if callee.sym != *"URL" {
+        return None;
+    }
+    let args = new.args.as_ref()?;
+    let is_import_meta_url = args.get(1).is_some_and(|arg| match &*arg.expr {
+        swc_ecma_ast::Expr::Member(member) => {
+            matches!(
+                &*member.obj,
+                swc_ecma_ast::Expr::MetaProp(meta)
+                    if meta.kind == swc_ecma_ast::MetaPropKind::ImportMeta
+            ) && matches!(&member.prop, swc_ecma_ast::MemberProp::Ident(prop) if prop.sym == *"url")
+        }
+        _ => false,
+    });
+    if !is_import_meta_url {
+        return None;
+    }
+    match args.first().map(|arg| &*arg.expr) {
+        Some(swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(s))) => to_request(&s.value),
+        _ => None,
+    }
+}
+
+/// worker 的地址参数，支持字符串和 `new URL(..., import.meta.url)`
+fn worker_request(expr: &swc_ecma_ast::Expr) -> Option<String> {
+    match expr {
+        swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(s)) => to_request(&s.value),
+        swc_ecma_ast::Expr::New(new) => import_meta_url_request(new),
+        swc_ecma_ast::Expr::Paren(paren) => worker_request(&paren.expr),
+        _ => None,
+    }
+}
+
+/// 对象字面量中 `key` 属性的值
+fn object_prop<'a>(obj: &'a swc_ecma_ast::ObjectLit, key: &str) -> Option<&'a swc_ecma_ast::Expr> {
+    obj.props.iter().find_map(|prop| match prop {
+        swc_ecma_ast::PropOrSpread::Prop(prop) => match &**prop {
+            swc_ecma_ast::Prop::KeyValue(kv) => {
+                let matched = match &kv.key {
+                    swc_ecma_ast::PropName::Ident(ident) => ident.sym == *key,
+                    swc_ecma_ast::PropName::Str(s) => s.value == *key,
+                    _ => false,
+                };
+                matched.then_some(&*kv.value)
+            }
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// 导入属性 `with { type: 'json' }`，记为 `with.type` 这样的 metadata
+fn import_attributes(with: &swc_ecma_ast::ObjectLit) -> BTreeMap<String, String> {
+    with.props
+        .iter()
+        .filter_map(|prop| match prop {
+            swc_ecma_ast::PropOrSpread::Prop(prop) => match &**prop {
+                swc_ecma_ast::Prop::KeyValue(kv) => {
+                    let key = match &kv.key {
+                        swc_ecma_ast::PropName::Ident(ident) => ident.sym.to_string(),
+                        swc_ecma_ast::PropName::Str(s) => s.value.to_string(),
+                        _ => return None,
+                    };
+                    match &*kv.value {
+                        swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(value)) => {
+                            Some((format!("with.{}", key), value.value.to_string()))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// 是否为立即执行的函数，如 `(function () {})()` 或 UMD 中的 `(function () {}).call(this)`
+fn is_immediately_invoked(callee: &Callee) -> bool {
+    let Callee::Expr(callee) = callee else {
+        return false;
+    };
+    match &**callee {
+        swc_ecma_ast::Expr::Member(member) => {
+            is_function(&member.obj)
+                && matches!(
+                    &member.prop,
+                    swc_ecma_ast::MemberProp::Ident(prop) if prop.sym == *"call" || prop.sym == *"apply"
+                )
+        }
+        expr => is_function(expr),
+    }
+}
+
+/// 是否为函数表达式或箭头函数
+fn is_function(expr: &swc_ecma_ast::Expr) -> bool {
+    match expr {
+        swc_ecma_ast::Expr::Fn(_) | swc_ecma_ast::Expr::Arrow(_) => true,
+        swc_ecma_ast::Expr::Paren(paren) => is_function(&paren.expr),
+        _ => false,
+    }
+}
+
+/// 函数表达式或箭头函数的参数
+fn function_params(expr: &swc_ecma_ast::Expr) -> Vec<&Pat> {
+    match expr {
+        swc_ecma_ast::Expr::Fn(func) => func
+            .function
+            .params
+            .iter()
+            .map(|param| &param.pat)
+            .collect(),
+        swc_ecma_ast::Expr::Arrow(arrow) => arrow.params.iter().collect(),
+        swc_ecma_ast::Expr::Paren(paren) => function_params(&paren.expr),
+        _ => vec![],
+    }
+}
+
+/// AMD 依赖对应的请求，`require`、`exports`、`module` 不是模块，`text!./a.html` 去掉加载插件
+fn amd_request(request: &str) -> Option<String> {
+    if matches!(request, "require" | "exports" | "module") {
+        return None;
+    }
+    let resource = request
+        .split_once('!')
+        .map_or(request, |(_, resource)| resource);
+    (!resource.is_empty()).then(|| resource.to_string())
+}
+
+/// 是否为 `createRequire(...)` 或 `module.createRequire(...)`
+fn is_create_require_call(call: &swc_ecma_ast::CallExpr) -> bool {
+    let Callee::Expr(ref callee) = call.callee else {
+        return false;
+    };
+    match &**callee {
+        swc_ecma_ast::Expr::Ident(ident) => ident.sym == *"createRequire",
+        swc_ecma_ast::Expr::Member(member) => matches!(
+            &member.prop,
+            swc_ecma_ast::MemberProp::Ident(prop) if prop.sym == *"createRequire"
+        ),
+        _ => false,
+    }
+}
+
+/// jest / vitest 中以模块路径为参数的 mock 方法
+fn is_test_mock_call(obj: &str, method: &str) -> bool {
+    match obj {
+        "jest" => matches!(
+            method,
+            "mock" | "unmock" | "doMock" | "dontMock" | "setMock" | "requireActual" | "requireMock"
+        ),
+        "vi" => matches!(
+            method,
+            "mock" | "unmock" | "doMock" | "doUnmock" | "importActual" | "importMock"
+        ),
+        _ => false,
+    }
+}
+
+/// 是否为 `navigator.serviceWorker.register`
+fn is_service_worker_register(member: &swc_ecma_ast::MemberExpr) -> bool {
+    let swc_ecma_ast::MemberProp::Ident(ref prop) = member.prop else {
+        return false;
+    };
+    prop.sym == *"register"
+        && matches!(
+            &*member.obj,
+            swc_ecma_ast::Expr::Member(obj)
+                if matches!(&obj.prop, swc_ecma_ast::MemberProp::Ident(prop) if prop.sym == *"serviceWorker")
+        )
+}
+
+/// 判断 import.meta.glob 的第二个参数是否为 `{ eager: true }`
+fn is_eager_option(expr: &swc_ecma_ast::Expr) -> bool {
+    let swc_ecma_ast::Expr::Object(obj) = expr else {
+        return false;
+    };
+    matches!(
+        object_prop(obj, "eager"),
+        Some(swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Bool(b))) if b.value
+    )
+}
+
+/// 收集在值位置被引用的标识符，跳过类型注解、interface、type alias 等类型位置
+pub struct ValueRefCollector<'a> {
+    pub refs: &'a mut HashSet<String>,
+}
+
+impl<'a> Visit for ValueRefCollector<'a> {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.refs.insert(ident.sym.to_string());
+    }
+
+    fn visit_jsx_element(&mut self, element: &swc_ecma_ast::JSXElement) {
+        // classic JSX runtime 会隐式引用 React
+        self.refs.insert("React".to_string());
+        element.visit_children_with(self);
+    }
+
+    fn visit_jsx_fragment(&mut self, fragment: &swc_ecma_ast::JSXFragment) {
+        self.refs.insert("React".to_string());
+        fragment.visit_children_with(self);
+    }
+
+    fn visit_import_decl(&mut self, _: &swc_ecma_ast::ImportDecl) {}
+
+    fn visit_named_export(&mut self, export: &swc_ecma_ast::NamedExport) {
+        if export.src.is_some() || export.type_only {
+            return;
+        }
+        for specifier in &export.specifiers {
+            if let swc_ecma_ast::ExportSpecifier::Named(named) = specifier {
+                if let ModuleExportName::Ident(ident) = &named.orig {
+                    if !named.is_type_only {
+                        self.refs.insert(ident.sym.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_ts_type(&mut self, _: &swc_ecma_ast::TsType) {}
+
+    fn visit_ts_interface_decl(&mut self, _: &swc_ecma_ast::TsInterfaceDecl) {}
+
+    fn visit_ts_type_alias_decl(&mut self, _: &swc_ecma_ast::TsTypeAliasDecl) {}
+
+    fn visit_ts_expr_with_type_args(&mut self, _: &swc_ecma_ast::TsExprWithTypeArgs) {}
+}
+
+/// 收集引用了顶层声明或 import 的标识符
+///
+/// 通过 resolver 标记的 SyntaxContext 区分作用域，同名的参数、局部变量和全局变量都不会被收集，
+/// 成员属性和对象字面量的 key 不是 Ident，也不会被收集
 pub struct IdentCollector<'a> {
     pub idents: &'a mut Vec<String>,
+    pub top_level_ctxt: SyntaxContext,
 }
 
 impl<'a> Visit for IdentCollector<'a> {
     fn visit_ident(&mut self, ident: &Ident) {
-        self.idents.push(ident.sym.to_string());
+        if ident.ctxt == self.top_level_ctxt {
+            self.idents.push(ident.sym.to_string());
+        }
     }
 }
 
 impl Visit for DependencyCollector {
+    fn visit_program(&mut self, program: &swc_ecma_ast::Program) {
+        let first_item = match program {
+            swc_ecma_ast::Program::Module(module) => module.body.first().map(|item| item.span_lo()),
+            swc_ecma_ast::Program::Script(script) => script.body.first().map(|stmt| stmt.span_lo()),
+        };
+        self.collect_triple_slash_directives(first_item);
+        if self.collect_symbol {
+            self.collect_local_kinds(program);
+        }
+
+        if self.is_typescript() {
+            let mut collector = ValueRefCollector {
+                refs: &mut self.value_refs,
+            };
+            program.visit_with(&mut collector);
+        }
+        program.visit_children_with(self);
+        self.apply_ignore_directives();
+    }
+
+    fn visit_ts_import_equals_decl(&mut self, decl: &swc_ecma_ast::TsImportEqualsDecl) {
+        // import fs = require('fs')，`import A = B.C` 不是模块引用
+        let swc_ecma_ast::TsModuleRef::TsExternalModuleRef(ref module_ref) = decl.module_ref else {
+            return;
+        };
+        let request = module_ref.expr.value.to_string();
+        self.add_dependency(request.clone(), DependencyKind::ImportEquals, decl.span)
+            .type_only = decl.is_type_only;
+
+        if !self.collect_symbol {
+            return;
+        }
+        let local = decl.id.sym.to_string();
+        self.add_import(local.clone(), "*".to_string(), request, decl.is_type_only);
+        if decl.is_export {
+            let depends_on = self.find_import_ids(&local);
+            self.exports.push(ExportSymbol {
+                local: local.clone(),
+                exported: local,
+                reexport_source: None,
+                reexport_id: None,
+                depends_on,
+                kind: SymbolKind::Namespace,
+            });
+        }
+    }
+
+    fn visit_ts_export_assignment(&mut self, export: &swc_ecma_ast::TsExportAssignment) {
+        // export = require('./foo')
+        if let swc_ecma_ast::Expr::Call(ref call) = *export.expr {
+            if let swc_ecma_ast::Callee::Expr(ref callee) = call.callee {
+                if self.require_kind(callee).is_some() {
+                    if let Some(arg) = call.args.first() {
+                        if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) = *arg.expr {
+                            let request = s.value.to_string();
+                            self.add_dependency(
+                                request.clone(),
+                                DependencyKind::ExportEquals,
+                                export.span,
+                            );
+                            if self.collect_symbol {
+                                self.exports.push(ExportSymbol {
+                                    local: "*".to_string(),
+                                    exported: "default".to_string(),
+                                    reexport_source: Some(request),
+                                    reexport_id: None,
+                                    depends_on: vec![],
+                                    kind: SymbolKind::Value,
+                                });
+                            }
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.collect_symbol {
+            // export = Foo，等价于 CommonJS 的 module.exports = Foo
+            let local = match &*export.expr {
+                swc_ecma_ast::Expr::Ident(ident) => ident.sym.to_string(),
+                _ => "default".to_string(),
+            };
+            self.add_local_export(
+                &export.expr,
+                local,
+                "default".to_string(),
+                SymbolKind::Value,
+            );
+        }
+        export.visit_children_with(self);
+    }
+
     fn visit_import_decl(&mut self, import: &swc_ecma_ast::ImportDecl) {
         // 处理静态导入
         let request = import.src.value.to_string();
-        let dependency = Dependency {
-            issuer: self.path.to_string_lossy().to_string(),
-            request: request.clone(),
-            kind: DependencyKind::StaticImport,
-            id: Some(self.id.clone()),
-        };
-        self.dependencies.push(dependency);
+        let type_only = self.is_type_only_import(import);
+        let dependency =
+            self.add_dependency(request.clone(), DependencyKind::StaticImport, import.span);
+        dependency.type_only = type_only;
+        if let Some(with) = &import.with {
+            dependency.metadata = import_attributes(with);
+        }
 
         if !self.collect_symbol {
             return;
@@ -157,134 +1440,408 @@ impl Visit for DependencyCollector {
                         Some(ModuleExportName::Str(s)) => s.value.to_string(),
                         None => local.clone(),
                     };
-                    self.add_import(local, imported, request.clone());
+                    let type_only = import.type_only
+                        || named.is_type_only
+                        || self.is_type_only_binding(&named.local);
+                    self.add_import(local, imported, request.clone(), type_only);
                 }
                 ImportSpecifier::Default(default) => {
+                    let type_only = import.type_only || self.is_type_only_binding(&default.local);
                     self.add_import(
                         default.local.sym.to_string(),
                         "default".to_string(),
                         request.clone(),
+                        type_only,
                     );
                 }
                 ImportSpecifier::Namespace(ns) => {
-                    self.add_import(ns.local.sym.to_string(), "*".to_string(), request.clone());
+                    let type_only = import.type_only || self.is_type_only_binding(&ns.local);
+                    self.add_import(
+                        ns.local.sym.to_string(),
+                        "*".to_string(),
+                        request.clone(),
+                        type_only,
+                    );
+                }
+            }
+        }
+    }
+
+    fn visit_assign_expr(&mut self, assign: &swc_ecma_ast::AssignExpr) {
+        if self.collect_symbol && assign.op == swc_ecma_ast::AssignOp::Assign {
+            if let Some(member) = assign.left.as_simple().and_then(|left| left.as_member()) {
+                // exports.a = exports.b = void 0 取最右侧的值
+                let mut value = &*assign.right;
+                while let swc_ecma_ast::Expr::Assign(inner) = value {
+                    value = &inner.right;
+                }
+                let is_void = matches!(
+                    value,
+                    swc_ecma_ast::Expr::Unary(unary) if unary.op == swc_ecma_ast::UnaryOp::Void
+                );
+                if self.is_module_exports(member) {
+                    // module.exports = ...
+                    self.add_cjs_module_exports(value);
+                } else if self.is_cjs_exports(&member.obj) && !is_void {
+                    // exports.foo = ... 或 module.exports.foo = ...
+                    if let Some(exported) = member_prop_name(&member.prop) {
+                        if exported != "__esModule" {
+                            self.add_cjs_export(exported, value);
+                        }
+                    }
+                }
+            }
+        }
+        assign.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, expr: &swc_ecma_ast::CallExpr) {
+        if self.collect_symbol {
+            self.collect_cjs_export_call(expr);
+        }
+        let first_dependency = self.dependencies.len();
+        if let Callee::Import(_) = &expr.callee {
+            if let Some(arg) = expr.args.get(0) {
+                if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) = *arg.expr {
+                    let request = s.value.to_string();
+                    if !self.skip_dynamic_imports {
+                        self.add_dependency(
+                            request.clone(),
+                            DependencyKind::DynamicImport,
+                            expr.span,
+                        );
+                    }
+
+                    let id = if let Some(existing_id) =
+                        self.dynamic_import_expr_to_id_map.get(&request)
+                    {
+                        *existing_id
+                    } else {
+                        let new_id = self.next_import_id;
+                        self.next_import_id += 1;
+                        self.dynamic_import_expr_to_id_map
+                            .insert(request.clone(), new_id);
+                        new_id
+                    };
+
+                    let exists = self.imports.iter().any(|imp| imp.id == id);
+
+                    if !exists {
+                        let symbol = ImportSymbol {
+                            id,
+                            local: format!("__dynamic_import_{}", id),
+                            imported: "*".to_string(),
+                            source: request.clone(),
+                            type_only: false,
+                            source_id: None,
+                            origin: None,
+                        };
+                        self.imports.push(symbol);
+                    }
+                } else if !self.skip_dynamic_imports {
+                    self.add_context_dependencies(
+                        &arg.expr,
+                        DependencyKind::DynamicImport,
+                        expr.span,
+                    );
+                }
+            }
+        }
+
+        if let swc_ecma_ast::Callee::Expr(ref callee_expr) = expr.callee {
+            if self.visit_amd_call(expr, callee_expr) {
+                return;
+            }
+
+            // 处理 CommonJS 导入
+            if let Some(kind) = self.require_kind(callee_expr) {
+                if let Some(arg) = expr.args.first() {
+                    if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) = *arg.expr {
+                        let request = s.value.to_string();
+                        self.add_dependency(request, kind, expr.span);
+                    } else {
+                        self.add_context_dependencies(&arg.expr, kind, expr.span);
+                    }
+                }
+            }
+
+            // importScripts('./a.js', './b.js')
+            if let swc_ecma_ast::Expr::Ident(ref ident) = &**callee_expr {
+                if ident.sym == *"importScripts" {
+                    for arg in &expr.args {
+                        if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) = *arg.expr {
+                            if let Some(request) = to_request(&s.value) {
+                                self.add_dependency(
+                                    request,
+                                    DependencyKind::ImportScripts,
+                                    arg.span(),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+
+            // navigator.serviceWorker.register('./sw.js')
+            if let swc_ecma_ast::Expr::Member(ref member) = &**callee_expr {
+                if is_service_worker_register(member) {
+                    if let Some(request) =
+                        expr.args.first().and_then(|arg| worker_request(&arg.expr))
+                    {
+                        self.add_dependency(request, DependencyKind::ServiceWorker, expr.span);
+                        // 地址中的 new URL() 已经作为 worker 处理
+                        for arg in expr.args.iter().skip(1) {
+                            arg.visit_with(self);
+                        }
+                        return;
+                    }
+                }
+            }
+
+            // 处理 require.context / import.meta.glob
+            if let swc_ecma_ast::Expr::Member(ref member) = &**callee_expr {
+                if let swc_ecma_ast::MemberProp::Ident(ref prop) = member.prop {
+                    match &*member.obj {
+                        swc_ecma_ast::Expr::Ident(obj)
+                            if self.is_global_require(obj) && prop.sym == *"context" =>
+                        {
+                            self.visit_require_context(expr);
+                        }
+                        swc_ecma_ast::Expr::Ident(obj)
+                            if is_test_mock_call(&obj.sym, &prop.sym) =>
+                        {
+                            if let Some(arg) = expr.args.first() {
+                                if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) =
+                                    *arg.expr
+                                {
+                                    self.add_dependency(
+                                        s.value.to_string(),
+                                        DependencyKind::TestMock,
+                                        expr.span,
+                                    );
+                                }
+                            }
+                        }
+                        swc_ecma_ast::Expr::MetaProp(meta)
+                            if meta.kind == swc_ecma_ast::MetaPropKind::ImportMeta =>
+                        {
+                            match &*prop.sym {
+                                "glob" => self.visit_import_meta_glob(expr, false),
+                                "globEager" => self.visit_import_meta_glob(expr, true),
+                                _ => {}
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        // import(/* webpackChunkName: "a" */ './a', { with: { type: 'json' } })
+        if let Some(arg) = expr.args.first() {
+            let mut metadata = self.magic_comments(arg.span_lo());
+            if let Some(options) = expr.args.get(1) {
+                if let swc_ecma_ast::Expr::Object(ref options) = *options.expr {
+                    if let Some(swc_ecma_ast::Expr::Object(with)) = object_prop(options, "with") {
+                        metadata.extend(import_attributes(with));
+                    }
                 }
             }
+            for dependency in &mut self.dependencies[first_dependency..] {
+                dependency.metadata.extend(metadata.clone());
+            }
         }
+
+        self.immediate_function = is_immediately_invoked(&expr.callee);
+        expr.visit_children_with(self);
     }
 
-    fn visit_call_expr(&mut self, expr: &swc_ecma_ast::CallExpr) {
-        if let Callee::Import(_) = &expr.callee {
-            if let Some(arg) = expr.args.get(0) {
-                if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) = *arg.expr {
-                    let request = s.value.to_string();
-                    let dependency = Dependency {
-                        issuer: self.path.to_string_lossy().to_string(),
-                        request: request.clone(),
-                        kind: DependencyKind::DynamicImport,
-                        id: Some(self.id.clone()),
-                    };
-                    if !self.skip_dynamic_imports {
-                        self.dependencies.push(dependency);
-                    }
+    fn visit_try_stmt(&mut self, stmt: &swc_ecma_ast::TryStmt) {
+        // try { require('optional') } catch { require('fallback') }
+        self.try_depth += 1;
+        stmt.block.visit_with(self);
+        self.try_depth -= 1;
+        self.visit_conditional(|this| stmt.handler.visit_with(this));
+        stmt.finalizer.visit_with(self);
+    }
 
-                    let id = if let Some(existing_id) =
-                        self.dynamic_import_expr_to_id_map.get(&request)
-                    {
-                        *existing_id
-                    } else {
-                        let new_id = self.next_import_id;
-                        self.next_import_id += 1;
-                        self.dynamic_import_expr_to_id_map
-                            .insert(request.clone(), new_id);
-                        new_id
-                    };
+    fn visit_if_stmt(&mut self, stmt: &swc_ecma_ast::IfStmt) {
+        stmt.test.visit_with(self);
+        self.visit_conditional(|this| {
+            stmt.cons.visit_with(this);
+            stmt.alt.visit_with(this);
+        });
+    }
 
-                    let exists = self.imports.iter().any(|imp| imp.id == id);
+    fn visit_cond_expr(&mut self, expr: &swc_ecma_ast::CondExpr) {
+        expr.test.visit_with(self);
+        self.visit_conditional(|this| {
+            expr.cons.visit_with(this);
+            expr.alt.visit_with(this);
+        });
+    }
 
-                    if !exists {
-                        let symbol = ImportSymbol {
-                            id,
-                            local: format!("__dynamic_import_{}", id),
-                            imported: "*".to_string(),
-                            source: request.clone(),
-                        };
-                        self.imports.push(symbol);
-                    }
-                }
-            }
+    fn visit_bin_expr(&mut self, expr: &swc_ecma_ast::BinExpr) {
+        // process.env.X && require('./dev-only')
+        if matches!(
+            expr.op,
+            swc_ecma_ast::BinaryOp::LogicalAnd
+                | swc_ecma_ast::BinaryOp::LogicalOr
+                | swc_ecma_ast::BinaryOp::NullishCoalescing
+        ) {
+            expr.left.visit_with(self);
+            self.visit_conditional(|this| expr.right.visit_with(this));
+        } else {
+            expr.visit_children_with(self);
         }
+    }
 
-        if let swc_ecma_ast::Callee::Expr(ref callee_expr) = expr.callee {
-            // 处理 CommonJS 导入
-            if let swc_ecma_ast::Expr::Ident(ref ident) = &**callee_expr {
-                if ident.sym == *"require" {
-                    if let Some(arg) = expr.args.get(0) {
-                        if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(ref s)) = *arg.expr {
-                            let request = s.value.to_string();
-                            let dependency = Dependency {
-                                issuer: self.path.to_string_lossy().to_string(),
-                                request,
-                                kind: DependencyKind::CommonJS,
-                                id: Some(self.id.clone()),
-                            };
-                            self.dependencies.push(dependency);
-                        }
+    fn visit_switch_case(&mut self, case: &swc_ecma_ast::SwitchCase) {
+        case.test.visit_with(self);
+        self.visit_conditional(|this| case.cons.visit_with(this));
+    }
+
+    fn visit_function(&mut self, function: &swc_ecma_ast::Function) {
+        self.visit_function_body(|this| function.visit_children_with(this));
+    }
+
+    fn visit_arrow_expr(&mut self, arrow: &swc_ecma_ast::ArrowExpr) {
+        self.visit_function_body(|this| arrow.visit_children_with(this));
+    }
+
+    fn visit_constructor(&mut self, constructor: &swc_ecma_ast::Constructor) {
+        self.visit_function_body(|this| constructor.visit_children_with(this));
+    }
+
+    fn visit_getter_prop(&mut self, getter: &swc_ecma_ast::GetterProp) {
+        self.visit_function_body(|this| getter.visit_children_with(this));
+    }
+
+    fn visit_setter_prop(&mut self, setter: &swc_ecma_ast::SetterProp) {
+        self.visit_function_body(|this| setter.visit_children_with(this));
+    }
+
+    fn visit_class_prop(&mut self, prop: &swc_ecma_ast::ClassProp) {
+        // 静态属性在定义类时执行，实例属性在构造时执行
+        if prop.is_static {
+            prop.visit_children_with(self);
+        } else {
+            self.visit_function_body(|this| prop.visit_children_with(this));
+        }
+    }
+
+    fn visit_new_expr(&mut self, expr: &swc_ecma_ast::NewExpr) {
+        // new Worker(new URL('./worker.ts', import.meta.url)) / new SharedWorker('./worker.js')
+        if let swc_ecma_ast::Expr::Ident(ref ident) = *expr.callee {
+            if ident.sym == *"Worker" || ident.sym == *"SharedWorker" {
+                let args = expr.args.as_deref().unwrap_or_default();
+                if let Some(request) = args.first().and_then(|arg| worker_request(&arg.expr)) {
+                    self.add_dependency(request, DependencyKind::Worker, expr.span);
+                    for arg in args.iter().skip(1) {
+                        arg.visit_with(self);
                     }
+                    return;
                 }
             }
         }
+
+        // new URL('./logo.svg', import.meta.url)
+        if let Some(request) = import_meta_url_request(expr) {
+            self.add_dependency(request, DependencyKind::AssetUrl, expr.span);
+        }
         expr.visit_children_with(self);
     }
 
     fn visit_export_all(&mut self, node: &swc_ecma_ast::ExportAll) {
         let request = node.src.value.to_string();
-        let dependency = Dependency {
-            issuer: self.path.to_string_lossy().to_string(),
-            request: request.clone(),
-            kind: DependencyKind::StaticExport,
-            id: Some(self.id.clone()),
-        };
-        self.dependencies.push(dependency);
+        let dependency =
+            self.add_dependency(request.clone(), DependencyKind::StaticExport, node.span);
+        dependency.type_only = node.type_only;
+        if let Some(with) = &node.with {
+            dependency.metadata = import_attributes(with);
+        }
 
         if self.collect_symbol {
             self.exports.push(ExportSymbol {
                 local: "*".to_string(),
                 exported: "*".to_string(),
                 reexport_source: Some(request),
+                reexport_id: None,
                 depends_on: vec![],
+                kind: match node.type_only {
+                    true => SymbolKind::Type,
+                    false => SymbolKind::Value,
+                },
             });
         }
         node.visit_children_with(self);
     }
 
+    fn visit_var_declarator(&mut self, decl: &swc_ecma_ast::VarDeclarator) {
+        // const require = createRequire(import.meta.url)
+        if let (Pat::Ident(binding), Some(swc_ecma_ast::Expr::Call(call))) =
+            (&decl.name, decl.init.as_deref())
+        {
+            if is_create_require_call(call) {
+                self.require_bindings
+                    .insert(binding.id.to_id(), DependencyKind::CreateRequire);
+            }
+        }
+        decl.visit_children_with(self);
+    }
+
     fn visit_var_decl(&mut self, var: &swc_ecma_ast::VarDecl) {
+        // const BASE = './pages'，用于计算 require(BASE + '/a') 的地址
+        if var.kind == swc_ecma_ast::VarDeclKind::Const {
+            for decl in &var.decls {
+                if let (Pat::Ident(binding), Some(init)) = (&decl.name, &decl.init) {
+                    if let Some(value) = self.eval_string(init) {
+                        self.string_consts.insert(binding.id.to_id(), value);
+                    }
+                }
+            }
+        }
+
         if !self.collect_symbol {
             return var.visit_children_with(self);
         }
 
         for decl in &var.decls {
-            let local = match &decl.name {
-                Pat::Ident(binding) => binding.id.sym.to_string(),
-                Pat::Object(_) | Pat::Array(_) => "<destructured>".to_string(),
-                _ => "<unknown>".to_string(),
-            };
-
-            let mut used_idents = vec![];
+            let mut init_idents = vec![];
             if let Some(init) = &decl.init {
-                let mut collector = IdentCollector {
-                    idents: &mut used_idents,
-                };
-                init.visit_with(&mut collector);
+                init.visit_with(&mut IdentCollector {
+                    idents: &mut init_idents,
+                    top_level_ctxt: self.top_level_ctxt,
+                });
             }
+            // const foo = require('./foo')、const { a, b: c } = require('./foo')
+            let require = decl
+                .init
+                .as_deref()
+                .and_then(|init| self.require_request(init));
 
-            self.local_symbol_map.insert(local.clone(), used_idents);
+            for binding in pat_bindings(&decl.name, self.top_level_ctxt) {
+                match &require {
+                    Some((request, imported)) => {
+                        let imported = match binding.key {
+                            Some(key) if imported == "*" => key,
+                            _ => imported.clone(),
+                        };
+                        self.add_import(binding.local, imported, request.clone(), false);
+                    }
+                    None if binding.top_level => {
+                        let mut used_idents = init_idents.clone();
+                        used_idents.extend(binding.used_idents);
+                        self.local_symbol_map.insert(binding.local, used_idents);
+                    }
+                    None => {}
+                }
+            }
         }
         var.visit_children_with(self);
     }
 
     fn visit_fn_decl(&mut self, func: &swc_ecma_ast::FnDecl) {
-        if !self.collect_symbol {
+        if !self.collect_symbol || func.ident.ctxt != self.top_level_ctxt {
             return func.visit_children_with(self);
         }
 
@@ -292,6 +1849,7 @@ impl Visit for DependencyCollector {
         let mut used_idents = vec![];
         func.function.visit_with(&mut IdentCollector {
             idents: &mut used_idents,
+            top_level_ctxt: self.top_level_ctxt,
         });
         self.local_symbol_map.insert(local, used_idents);
 
@@ -299,7 +1857,7 @@ impl Visit for DependencyCollector {
     }
 
     fn visit_class_decl(&mut self, class: &swc_ecma_ast::ClassDecl) {
-        if !self.collect_symbol {
+        if !self.collect_symbol || class.ident.ctxt != self.top_level_ctxt {
             return class.visit_children_with(self);
         }
 
@@ -307,6 +1865,7 @@ impl Visit for DependencyCollector {
         let mut used_idents = vec![];
         class.class.visit_with(&mut IdentCollector {
             idents: &mut used_idents,
+            top_level_ctxt: self.top_level_ctxt,
         });
         self.local_symbol_map.insert(local, used_idents);
 
@@ -314,13 +1873,30 @@ impl Visit for DependencyCollector {
     }
 
     fn visit_named_export(&mut self, export: &swc_ecma_ast::NamedExport) {
+        // export { foo as bar } from './mod'
+        let request = export.src.as_ref().map(|src| src.value.to_string());
+
+        if let Some(req) = &request {
+            let type_only = export.type_only
+                || (!export.specifiers.is_empty()
+                    && export.specifiers.iter().all(|specifier| {
+                        matches!(
+                            specifier,
+                            swc_ecma_ast::ExportSpecifier::Named(named) if named.is_type_only
+                        )
+                    }));
+            let dependency =
+                self.add_dependency(req.clone(), DependencyKind::StaticExport, export.span);
+            dependency.type_only = type_only;
+            if let Some(with) = &export.with {
+                dependency.metadata = import_attributes(with);
+            }
+        }
+
         if !self.collect_symbol {
             return export.visit_children_with(self);
         }
 
-        // export { foo as bar } from './mod'
-        let request = export.src.as_ref().map(|src| src.value.to_string());
-
         for specifier in &export.specifiers {
             match specifier {
                 swc_ecma_ast::ExportSpecifier::Named(named) => {
@@ -340,23 +1916,33 @@ impl Visit for DependencyCollector {
                     } else {
                         self.find_import_ids(&local)
                     };
+                    // export type { A }，或者导出的是只作为类型使用的 import
+                    let type_only = export.type_only
+                        || named.is_type_only
+                        || (!depends_on.is_empty()
+                            && self
+                                .imports
+                                .iter()
+                                .filter(|import| depends_on.contains(&import.id))
+                                .all(|import| import.type_only));
+                    // 本地声明的 export { I, C } 使用声明的种类
+                    let local_kind = match request {
+                        Some(_) => None,
+                        None => self.local_kinds.get(&local).cloned(),
+                    };
 
                     self.exports.push(ExportSymbol {
                         local: local.clone(),
                         exported,
                         reexport_source: request.clone(),
+                        reexport_id: None,
                         depends_on,
+                        kind: match (type_only, local_kind) {
+                            (true, _) => SymbolKind::Type,
+                            (false, Some(kind)) => kind,
+                            (false, None) => SymbolKind::Value,
+                        },
                     });
-
-                    if let Some(req) = &request {
-                        let dependency = Dependency {
-                            issuer: self.path.to_string_lossy().to_string(),
-                            request: req.clone(),
-                            kind: DependencyKind::StaticExport,
-                            id: Some(self.id.clone()),
-                        };
-                        self.dependencies.push(dependency);
-                    }
                 }
                 swc_ecma_ast::ExportSpecifier::Default(_) => {
                     // 不太常见，忽略
@@ -371,18 +1957,10 @@ impl Visit for DependencyCollector {
                         local: "*".to_string(),
                         exported,
                         reexport_source: request.clone(),
+                        reexport_id: None,
                         depends_on: vec![],
+                        kind: SymbolKind::Namespace,
                     });
-
-                    if let Some(req) = &request {
-                        let dependency = Dependency {
-                            issuer: self.path.to_string_lossy().to_string(),
-                            request: req.clone(),
-                            kind: DependencyKind::StaticExport,
-                            id: Some(self.id.clone()),
-                        };
-                        self.dependencies.push(dependency);
-                    }
                 }
             }
         }
@@ -397,81 +1975,75 @@ impl Visit for DependencyCollector {
         match &export.decl {
             Decl::Class(class) => {
                 let local = class.ident.sym.to_string();
-                let mut used_idents = vec![];
-                let mut collector = IdentCollector {
-                    idents: &mut used_idents,
-                };
-                class.visit_with(&mut collector);
-                self.local_symbol_map
-                    .insert(local.clone(), used_idents.clone());
-
-                let depends_on = self.find_unique_import_ids_recursive(&used_idents);
-
-                self.exports.push(ExportSymbol {
-                    local: local.clone(),
-                    exported: local,
-                    reexport_source: None,
-                    depends_on,
-                });
+                let used_idents =
+                    self.add_local_export(class, local.clone(), local.clone(), SymbolKind::Class);
+                self.local_symbol_map.insert(local, used_idents);
             }
             Decl::Fn(func) => {
                 let local = func.ident.sym.to_string();
                 // 对函数体做 usage 收集
-                let mut used_idents = vec![];
-                let mut collector = IdentCollector {
-                    idents: &mut used_idents,
-                };
-                func.visit_with(&mut collector);
-                self.local_symbol_map
-                    .insert(local.clone(), used_idents.clone());
-
-                let depends_on = self.find_unique_import_ids_recursive(&used_idents);
-
-                self.exports.push(ExportSymbol {
-                    local: local.clone(),
-                    exported: local,
-                    reexport_source: None,
-                    depends_on,
-                });
+                let used_idents =
+                    self.add_local_export(func, local.clone(), local.clone(), SymbolKind::Function);
+                self.local_symbol_map.insert(local, used_idents);
             }
             Decl::Var(var) => {
+                // 先由 visit_var_decl 记录每个绑定
+                var.visit_with(self);
                 for decl in &var.decls {
-                    let local = match &decl.name {
-                        Pat::Ident(binding) => binding.id.sym.to_string(),
-                        Pat::Object(_) | Pat::Array(_) => "<destructured>".to_string(),
-                        _ => "<unknown>".to_string(),
+                    let dynamic_import_ids = match &decl.init {
+                        Some(init) => self.collect_dynamic_import_ids(init),
+                        None => vec![],
                     };
-
-                    let mut used_idents = vec![];
-                    if let Some(init) = &decl.init {
-                        let mut collector = IdentCollector {
-                            idents: &mut used_idents,
-                        };
-                        init.visit_with(&mut collector);
-                    }
-
-                    self.local_symbol_map
-                        .insert(local.clone(), used_idents.clone());
-                    let mut depends_on = self.find_unique_import_ids_recursive(&[local.clone()]);
-
-                    // 正确在这里拼 dynamic import
-                    if let Some(init) = &decl.init {
-                        depends_on.extend(self.collect_dynamic_import_ids(init));
+                    for binding in pat_bindings(&decl.name, self.top_level_ctxt) {
+                        let mut depends_on = self
+                            .find_unique_import_ids_recursive(std::slice::from_ref(&binding.local));
+                        depends_on.extend(&dynamic_import_ids);
+                        self.exports.push(ExportSymbol {
+                            local: binding.local.clone(),
+                            exported: binding.local,
+                            reexport_source: None,
+                            reexport_id: None,
+                            depends_on,
+                            kind: SymbolKind::Variable,
+                        });
                     }
-                    self.exports.push(ExportSymbol {
-                        local: local.clone(),
-                        exported: local,
-                        reexport_source: None,
-                        depends_on,
-                    });
                 }
+                return;
+            }
+            // TypeScript 的 interface、type、enum 和 namespace，包括 export declare ...
+            decl => {
+                let (local, kind) = match decl {
+                    Decl::TsInterface(interface) => {
+                        (interface.id.sym.to_string(), SymbolKind::Type)
+                    }
+                    Decl::TsTypeAlias(alias) => (alias.id.sym.to_string(), SymbolKind::Type),
+                    Decl::TsEnum(ts_enum) => (ts_enum.id.sym.to_string(), SymbolKind::Enum),
+                    Decl::TsModule(module) => match &module.id {
+                        swc_ecma_ast::TsModuleName::Ident(ident) => {
+                            (ident.sym.to_string(), SymbolKind::Namespace)
+                        }
+                        // declare module 'foo' 声明的是其他模块
+                        swc_ecma_ast::TsModuleName::Str(_) => {
+                            return export.visit_children_with(self)
+                        }
+                    },
+                    _ => return export.visit_children_with(self),
+                };
+                let used_idents = self.add_local_export(decl, local.clone(), local.clone(), kind);
+                self.local_symbol_map.insert(local, used_idents);
             }
-            _ => {}
         }
 
         export.visit_children_with(self);
     }
 
+    fn visit_ts_module_decl(&mut self, decl: &swc_ecma_ast::TsModuleDecl) {
+        // namespace 和 declare module 内部的 export 不是当前模块的导出
+        let collect_symbol = std::mem::replace(&mut self.collect_symbol, false);
+        decl.visit_children_with(self);
+        self.collect_symbol = collect_symbol;
+    }
+
     fn visit_export_default_decl(&mut self, export: &swc_ecma_ast::ExportDefaultDecl) {
         if !self.collect_symbol {
             return export.visit_children_with(self);
@@ -489,22 +2061,15 @@ impl Visit for DependencyCollector {
                 .unwrap_or("default".to_string()),
             swc_ecma_ast::DefaultDecl::TsInterfaceDecl(_) => "default".to_string(),
         };
+        let kind = match &export.decl {
+            swc_ecma_ast::DefaultDecl::Class(_) => SymbolKind::Class,
+            swc_ecma_ast::DefaultDecl::Fn(_) => SymbolKind::Function,
+            swc_ecma_ast::DefaultDecl::TsInterfaceDecl(_) => SymbolKind::Type,
+        };
 
-        let mut used_idents = vec![];
-        export.decl.visit_with(&mut IdentCollector {
-            idents: &mut used_idents,
-        });
-        self.local_symbol_map
-            .insert(local.clone(), used_idents.clone());
-
-        let depends_on = self.find_unique_import_ids_recursive(&used_idents);
-
-        self.exports.push(ExportSymbol {
-            local: local.clone(),
-            exported: "default".to_string(),
-            reexport_source: None,
-            depends_on,
-        });
+        let used_idents =
+            self.add_local_export(&export.decl, local.clone(), "default".to_string(), kind);
+        self.local_symbol_map.insert(local, used_idents);
         export.visit_children_with(self);
     }
 
@@ -513,19 +2078,735 @@ impl Visit for DependencyCollector {
             return;
         }
 
-        let mut used_idents = vec![];
-        export.expr.visit_with(&mut IdentCollector {
-            idents: &mut used_idents,
-        });
-        let depends_on = self.find_unique_import_ids_recursive(&used_idents);
-
-        self.exports.push(ExportSymbol {
-            local: "default".to_string(),
-            exported: "default".to_string(),
-            reexport_source: None,
-            depends_on,
-        });
+        self.add_local_export(
+            &export.expr,
+            "default".to_string(),
+            "default".to_string(),
+            SymbolKind::Value,
+        );
 
         export.visit_children_with(self);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swc_core::common::{FileName, Globals, Mark, GLOBALS};
+    use swc_core::ecma::ast::EsVersion;
+    use swc_core::ecma::parser::{EsSyntax, Lexer, Parser, StringInput, Syntax, TsSyntax};
+    use swc_core::ecma::transforms::base::resolver;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    /// 按 `id` 的后缀解析源码并收集依赖，和 parse_tree_recursive 一样先标记作用域
+    fn collect(id: &str, source: &str) -> DependencyCollector {
+        collect_as(id, id, source)
+    }
+
+    /// 按 `syntax_id` 的后缀解析源码，如单文件组件 `App.vue` 中的 `<script lang="ts">`
+    fn collect_as(id: &str, syntax_id: &str, source: &str) -> DependencyCollector {
+        let source_map: Lrc<SourceMap> = Default::default();
+        let fm = source_map
+            .new_source_file(FileName::Real(PathBuf::from(id)).into(), source.to_string());
+        let comments = SingleThreadedComments::default();
+        let typescript = syntax_id.ends_with(".ts") || syntax_id.ends_with(".tsx");
+        let syntax = match typescript {
+            true => Syntax::Typescript(TsSyntax {
+                tsx: syntax_id.ends_with(".tsx"),
+                ..Default::default()
+            }),
+            false => Syntax::Es(EsSyntax {
+                jsx: true,
+                ..Default::default()
+            }),
+        };
+        let lexer = Lexer::new(
+            syntax,
+            EsVersion::EsNext,
+            StringInput::from(&*fm),
+            Some(&comments),
+        );
+        let mut program = Parser::new_from(lexer).parse_program().unwrap();
+
+        GLOBALS.set(&Globals::new(), || {
+            let unresolved_mark = Mark::new();
+            let top_level_mark = Mark::new();
+            program.visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, typescript));
+
+            let mut collector = DependencyCollector::new(
+                id.to_string(),
+                PathBuf::from(id),
+                source_map.clone(),
+                comments.clone(),
+                SyntaxContext::empty().apply_mark(unresolved_mark),
+                SyntaxContext::empty().apply_mark(top_level_mark),
+            );
+            collector.syntax_id = syntax_id.to_string();
+            collector.collect_symbol = true;
+            program.visit_with(&mut collector);
+            collector
+        })
+    }
+
+    fn edges(collector: &DependencyCollector) -> Vec<(&str, DependencyKind)> {
+        collector
+            .dependencies
+            .iter()
+            .map(|dep| (dep.request.as_str(), dep.kind.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_local_require_is_ignored() {
+        let collector = collect(
+            "index.js",
+            r#"
+            function load(require) { return require('./param'); }
+            { const require = () => {}; require('./local'); }
+            require('./global');
+            "#,
+        );
+        assert_eq!(
+            edges(&collector),
+            vec![("./global", DependencyKind::CommonJS)]
+        );
+    }
+
+    #[test]
+    fn test_umd_require_is_ignored() {
+        let collector = collect(
+            "index.js",
+            r#"
+            (function (root, factory) {
+                factory(root.require);
+            })(this, function (require) {
+                require('./umd');
+            });
+            "#,
+        );
+        assert_eq!(edges(&collector), vec![]);
+    }
+
+    #[test]
+    fn test_create_require_is_followed() {
+        let collector = collect(
+            "index.js",
+            r#"
+            import { createRequire } from 'module';
+            const req = createRequire(import.meta.url);
+            req('./bound');
+            createRequire(import.meta.url)('./inline');
+            function other(req) { req('./shadowed'); }
+            "#,
+        );
+        assert_eq!(
+            edges(&collector),
+            vec![
+                ("module", DependencyKind::StaticImport),
+                ("./bound", DependencyKind::CreateRequire),
+                ("./inline", DependencyKind::CreateRequire),
+            ]
+        );
+    }
+
+    fn depends_on(collector: &DependencyCollector, exported: &str) -> Vec<usize> {
+        let export = collector
+            .exports
+            .iter()
+            .find(|export| export.exported == exported)
+            .unwrap();
+        let mut depends_on = export.depends_on.clone();
+        depends_on.sort();
+        depends_on
+    }
+
+    #[test]
+    fn test_shadowed_idents_are_not_dependencies() {
+        let collector = collect(
+            "index.js",
+            r#"
+            import foo from './foo';
+            import bar from './bar';
+            function wrap(foo) { return foo; }
+            const obj = { foo: 1, bar() { const bar = 1; return bar; } };
+            export function a() { return wrap(1) + obj.foo + window.bar; }
+            export const b = (x) => foo(x);
+            export class C { foo() { return bar; } }
+            export function d() { function foo() {} class bar {} return foo() + bar; }
+            export const e = () => foo + bar;
+            "#,
+        );
+        assert!(depends_on(&collector, "a").is_empty());
+        assert_eq!(depends_on(&collector, "b"), vec![0]);
+        assert_eq!(depends_on(&collector, "C"), vec![1]);
+        assert!(depends_on(&collector, "d").is_empty());
+        assert_eq!(depends_on(&collector, "e"), vec![0, 1]);
+    }
+
+    fn type_only_edges(collector: &DependencyCollector) -> Vec<(&str, bool)> {
+        collector
+            .dependencies
+            .iter()
+            .map(|dep| (dep.request.as_str(), dep.type_only))
+            .collect()
+    }
+
+    #[test]
+    fn test_type_only_imports() {
+        let collector = collect(
+            "index.tsx",
+            r#"
+            import type { A } from './type-import';
+            import { type B, type C } from './inline-type';
+            import { type D, e } from './mixed';
+            import { F } from './type-usage';
+            import { G } from './value-usage';
+            import { H } from './export-type';
+            import { I } from './export-value';
+            import { J } from './implements';
+            import React from 'react';
+            import './side-effect';
+            export type { K } from './reexport-type';
+            export { type L } from './reexport-inline-type';
+
+            let f: F;
+            const g = G + e;
+            class Impl implements J {}
+            export type { H };
+            export { I };
+            export const el = <div />;
+            "#,
+        );
+        assert_eq!(
+            type_only_edges(&collector),
+            vec![
+                ("./type-import", true),
+                ("./inline-type", true),
+                ("./mixed", false),
+                ("./type-usage", true),
+                ("./value-usage", false),
+                ("./export-type", true),
+                ("./export-value", false),
+                ("./implements", true),
+                ("react", false),
+                ("./side-effect", false),
+                ("./reexport-type", true),
+                ("./reexport-inline-type", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imports_in_js_are_not_type_only() {
+        let collector = collect("index.js", "import { F } from './f';\n");
+        assert_eq!(type_only_edges(&collector), vec![("./f", false)]);
+    }
+
+    #[test]
+    fn test_type_only_imports_in_sfc_script() {
+        let source =
+            "import { Props } from './types';\nimport { ref } from 'vue';\nlet p: Props = ref();\n";
+        let collector = collect_as("App.vue", "App.vue.ts", source);
+        assert_eq!(
+            type_only_edges(&collector),
+            vec![("./types", true), ("vue", false)]
+        );
+
+        let collector = collect_as("App.vue", "App.vue.js", "import { F } from './f';\n");
+        assert_eq!(type_only_edges(&collector), vec![("./f", false)]);
+    }
+
+    fn ignored(collector: &DependencyCollector) -> Vec<(&str, Option<&str>)> {
+        collector
+            .dependencies
+            .iter()
+            .map(|dep| (dep.request.as_str(), dep.ignored.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_ignore_directives() {
+        let collector = collect(
+            "index.js",
+            r#"
+            // dpdm-ignore-next-line
+            import a from './next-line';
+            import b from './same-line'; /* dpdm-ignore lazy loaded */
+            import {
+                c,
+                d,
+            } from './multi-line'; // dpdm-ignore
+            import e from './kept';
+            "#,
+        );
+        assert_eq!(
+            ignored(&collector),
+            vec![
+                ("./next-line", Some("dpdm-ignore-next-line")),
+                ("./same-line", Some("lazy loaded")),
+                ("./multi-line", Some("dpdm-ignore")),
+                ("./kept", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignore_file_directive() {
+        let collector = collect(
+            "index.js",
+            r#"
+            /* dpdm-ignore-file generated code */
+            import a from './a';
+            require('./b');
+            "#,
+        );
+        assert_eq!(
+            ignored(&collector),
+            vec![
+                ("./a", Some("generated code")),
+                ("./b", Some("generated code")),
+            ]
+        );
+    }
+
+    fn imports(collector: &DependencyCollector) -> Vec<(&str, &str, &str)> {
+        collector
+            .imports
+            .iter()
+            .map(|import| {
+                (
+                    import.local.as_str(),
+                    import.imported.as_str(),
+                    import.source.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_destructured_exports() {
+        let collector = collect(
+            "index.js",
+            r#"
+            import obj from './obj';
+            import fallback from './fallback';
+            export const { a, b: { c }, d = fallback, ...rest } = obj;
+            export const [e, [f = fallback]] = [];
+            "#,
+        );
+        let exported: Vec<&str> = collector
+            .exports
+            .iter()
+            .map(|export| export.exported.as_str())
+            .collect();
+        assert_eq!(exported, vec!["a", "c", "d", "rest", "e", "f"]);
+        assert_eq!(depends_on(&collector, "a"), vec![0]);
+        assert_eq!(depends_on(&collector, "c"), vec![0]);
+        assert_eq!(depends_on(&collector, "d"), vec![0, 1]);
+        assert_eq!(depends_on(&collector, "rest"), vec![0]);
+        assert!(depends_on(&collector, "e").is_empty());
+        assert_eq!(depends_on(&collector, "f"), vec![1]);
+    }
+
+    #[test]
+    fn test_destructured_require() {
+        let collector = collect(
+            "index.js",
+            r#"
+            const { x, y: z, w: { v } } = require('./y');
+            const [first] = require('./list');
+            const all = require('./all');
+            "#,
+        );
+        assert_eq!(
+            imports(&collector),
+            vec![
+                ("x", "x", "./y"),
+                ("z", "y", "./y"),
+                ("v", "w", "./y"),
+                ("first", "*", "./list"),
+                ("all", "*", "./all"),
+            ]
+        );
+    }
+
+    fn requests(collector: &DependencyCollector) -> Vec<(&str, Option<&str>)> {
+        collector
+            .dependencies
+            .iter()
+            .map(|dep| (dep.request.as_str(), dep.id.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_requests() {
+        let collector = collect(
+            "src/index.js",
+            r#"
+            const path = require('path');
+            const BASE = './lib';
+            require(path.join(__dirname, 'a'));
+            require(__dirname + '/b');
+            import(BASE + '/x.js');
+            require(path.resolve(__dirname, 'c'));
+            require(path.resolve('d'));
+            "#,
+        );
+        // 相对路径的 path.resolve 基于运行时的 cwd，无法分析
+        assert_eq!(
+            requests(&collector),
+            vec![
+                ("path", Some("src/index.js")),
+                ("./a", Some("src/index.js")),
+                ("./b", Some("src/index.js")),
+                ("./lib/x.js", Some("src/index.js")),
+                ("./c", Some("src/index.js")),
+                ("path.resolve('d')", None),
+            ]
+        );
+    }
+
+    fn exports(collector: &DependencyCollector) -> Vec<(&str, &str, Option<&str>)> {
+        collector
+            .exports
+            .iter()
+            .map(|export| {
+                (
+                    export.local.as_str(),
+                    export.exported.as_str(),
+                    export.reexport_source.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cjs_exports() {
+        let collector = collect(
+            "index.js",
+            r#"
+            const a = require('./a');
+            const b = require('./b');
+            exports.foo = a;
+            exports.bar = require('./bar').baz;
+            module.exports = { b, c: a.c, d() { return b; }, ...require('./spread') };
+            Object.defineProperty(exports, '__esModule', { value: true });
+            Object.defineProperty(exports, 'e', { get() { return a; } });
+            __exportStar(require('./star'), exports);
+            tslib_1.__exportStar(b, exports);
+            "#,
+        );
+        assert_eq!(
+            exports(&collector),
+            vec![
+                ("a", "foo", None),
+                ("baz", "bar", Some("./bar")),
+                ("b", "b", None),
+                ("c", "c", None),
+                ("d", "d", None),
+                ("*", "*", Some("./spread")),
+                ("e", "e", None),
+                ("*", "*", Some("./star")),
+                ("*", "*", Some("./b")),
+            ]
+        );
+        assert_eq!(depends_on(&collector, "foo"), vec![0]);
+        assert_eq!(depends_on(&collector, "b"), vec![1]);
+        assert_eq!(depends_on(&collector, "c"), vec![0]);
+        assert_eq!(depends_on(&collector, "d"), vec![1]);
+        assert_eq!(depends_on(&collector, "e"), vec![0]);
+    }
+
+    /// (request, in_try, conditional, in_function, eager)
+    fn guards(collector: &DependencyCollector) -> Vec<(&str, bool, bool, bool, bool)> {
+        collector
+            .dependencies
+            .iter()
+            .map(|dep| {
+                (
+                    dep.request.as_str(),
+                    dep.in_try,
+                    dep.conditional,
+                    dep.in_function,
+                    dep.eager,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_guarded_requires() {
+        let collector = collect(
+            "index.js",
+            r#"
+            try { require('./try'); } catch (e) { require('./catch'); } finally { require('./finally'); }
+            if (process.env.DEV) require('./if');
+            process.env.DEV && require('./and');
+            options ?? require('./nullish');
+            process.env.DEV ? require('./ternary') : null;
+            function load() { try { require('./deferred'); } catch {} }
+            require('./top');
+            "#,
+        );
+        assert_eq!(
+            guards(&collector),
+            vec![
+                ("./try", true, false, false, true),
+                ("./catch", false, true, false, true),
+                ("./finally", false, false, false, true),
+                ("./if", false, true, false, true),
+                ("./and", false, true, false, true),
+                ("./nullish", false, true, false, true),
+                ("./ternary", false, true, false, true),
+                ("./deferred", true, false, true, false),
+                ("./top", false, false, false, true),
+            ]
+        );
+    }
+
+    fn eager(collector: &DependencyCollector) -> Vec<(&str, bool)> {
+        collector
+            .dependencies
+            .iter()
+            .map(|dep| (dep.request.as_str(), dep.eager))
+            .collect()
+    }
+
+    #[test]
+    fn test_eager_dependencies() {
+        let collector = collect(
+            "index.js",
+            r#"
+            (function () { require('./iife'); })();
+            (() => require('./arrow-iife'))();
+            (function () { require('./call'); }).call(this);
+            class A { static s = require('./static'); i = require('./instance'); }
+            const lazy = () => require('./arrow');
+            import('./dynamic');
+            "#,
+        );
+        assert_eq!(
+            eager(&collector),
+            vec![
+                ("./iife", true),
+                ("./arrow-iife", true),
+                ("./call", true),
+                ("./static", true),
+                ("./instance", false),
+                ("./arrow", false),
+                ("./dynamic", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_amd_dependencies() {
+        let collector = collect(
+            "index.js",
+            r#"
+            define('named', ['require', 'exports', './a', 'text!./tpl.html'], function (require, exports, a) {
+                require('./sugar-in-deps');
+            });
+            define(function (require) { require('./b'); });
+            require(['./lazy'], function (lazy) {});
+            "#,
+        );
+        assert_eq!(
+            edges(&collector),
+            vec![
+                ("./a", DependencyKind::Amd),
+                ("./tpl.html", DependencyKind::Amd),
+                ("./sugar-in-deps", DependencyKind::Amd),
+                ("./b", DependencyKind::Amd),
+                ("./lazy", DependencyKind::Amd),
+            ]
+        );
+        assert_eq!(
+            eager(&collector),
+            vec![
+                ("./a", true),
+                ("./tpl.html", true),
+                ("./sugar-in-deps", true),
+                ("./b", true),
+                ("./lazy", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_equals_and_triple_slash() {
+        let collector = collect(
+            "index.ts",
+            r#"/// <reference path="globals.d.ts" />
+            /// <reference types="node" />
+            import fs = require('fs');
+            import type T = require('./types');
+            export import util = require('./util');
+            import A = B.C;
+            /// <reference path="ignored.d.ts" />
+            "#,
+        );
+        assert_eq!(
+            edges(&collector),
+            vec![
+                ("./globals.d.ts", DependencyKind::TripleSlashPath),
+                ("node", DependencyKind::TripleSlashTypes),
+                ("fs", DependencyKind::ImportEquals),
+                ("./types", DependencyKind::ImportEquals),
+                ("./util", DependencyKind::ImportEquals),
+            ]
+        );
+        assert_eq!(
+            type_only_edges(&collector),
+            vec![
+                ("./globals.d.ts", true),
+                ("node", true),
+                ("fs", false),
+                ("./types", true),
+                ("./util", false),
+            ]
+        );
+        assert_eq!(exports(&collector), vec![("util", "util", None)]);
+        assert_eq!(depends_on(&collector, "util"), vec![2]);
+    }
+
+    #[test]
+    fn test_export_assignment() {
+        let collector = collect("index.ts", "export = require('./entry');\n");
+        assert_eq!(
+            edges(&collector),
+            vec![("./entry", DependencyKind::ExportEquals)]
+        );
+        assert_eq!(exports(&collector), vec![("*", "default", Some("./entry"))]);
+
+        let collector = collect("index.ts", "import { Foo } from './foo';\nexport = Foo;\n");
+        assert_eq!(exports(&collector), vec![("Foo", "default", None)]);
+        assert_eq!(depends_on(&collector, "default"), vec![0]);
+    }
+
+    fn metadata(collector: &DependencyCollector) -> Vec<BTreeMap<String, String>> {
+        collector
+            .dependencies
+            .iter()
+            .map(|dep| dep.metadata.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_magic_comments_and_import_attributes() {
+        let collector = collect(
+            "index.js",
+            r#"
+            import(/* webpackChunkName: "chunk-a", webpackPrefetch: true */ './a');
+            import(/* webpackInclude: /\.json$/ */ /* @vite-ignore */ './b');
+            import('./c.json', { with: { type: 'json' } });
+            import data from './d.json' with { type: 'json' };
+            export * from './e.css' with { 'type': 'css' };
+            "#,
+        );
+        let expected: Vec<BTreeMap<String, String>> = [
+            vec![("webpackChunkName", "chunk-a"), ("webpackPrefetch", "true")],
+            vec![("webpackInclude", r"/\.json$/"), ("@vite-ignore", "true")],
+            vec![("with.type", "json")],
+            vec![("with.type", "json")],
+            vec![("with.type", "css")],
+        ]
+        .into_iter()
+        .map(|pairs| {
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        })
+        .collect();
+        assert_eq!(metadata(&collector), expected);
+    }
+
+    #[test]
+    fn test_worker_and_asset_urls() {
+        let collector = collect(
+            "index.js",
+            r#"
+            new Worker(new URL('./worker.ts', import.meta.url), { type: 'module' });
+            new SharedWorker('./shared.js');
+            navigator.serviceWorker.register('/sw.js');
+            importScripts('./a.js', 'https://cdn.example.com/b.js');
+            const logo = new URL('./logo.svg', import.meta.url);
+            new URL('./not-an-asset.svg', location.href);
+            "#,
+        );
+        // 远程脚本和不基于 import.meta.url 的 URL 不是依赖
+        assert_eq!(
+            edges(&collector),
+            vec![
+                ("./worker.ts", DependencyKind::Worker),
+                ("./shared.js", DependencyKind::Worker),
+                ("./sw.js", DependencyKind::ServiceWorker),
+                ("./a.js", DependencyKind::ImportScripts),
+                ("./logo.svg", DependencyKind::AssetUrl),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_test_mocks() {
+        let collector = collect(
+            "index.test.js",
+            r#"
+            import { api } from '../api';
+            jest.mock('../api', () => ({ ...jest.requireActual('../api') }));
+            vi.mock('./store');
+            await vi.importActual('./real');
+            jest.fn();
+            mock.mock('./not-a-mock');
+            "#,
+        );
+        assert_eq!(
+            edges(&collector),
+            vec![
+                ("../api", DependencyKind::StaticImport),
+                ("../api", DependencyKind::TestMock),
+                ("../api", DependencyKind::TestMock),
+                ("./store", DependencyKind::TestMock),
+                ("./real", DependencyKind::TestMock),
+            ]
+        );
+    }
+
+    fn location(
+        start_line: usize,
+        start_column: usize,
+        end_line: usize,
+        end_column: usize,
+    ) -> Location {
+        Location {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+
+    #[test]
+    fn test_dependency_locations() {
+        let collector = collect(
+            "index.js",
+            "import a from './a';\nconst b = require('./b');\nimport(\n  './c'\n);\n",
+        );
+        let locations: Vec<Option<Location>> = collector
+            .dependencies
+            .iter()
+            .map(|dep| dep.loc.clone())
+            .collect();
+        // 行列号从 1 开始，结束位置不包含在内
+        assert_eq!(
+            locations,
+            vec![
+                Some(location(1, 1, 1, 21)),
+                Some(location(2, 11, 2, 25)),
+                Some(location(3, 1, 5, 2)),
+            ]
+        );
+        assert_eq!(location(2, 11, 2, 25).to_string(), "2:11");
+    }
+}
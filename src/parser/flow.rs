@@ -0,0 +1,242 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::ops::Range;
+
+lazy_static! {
+    // {| a: string |} 精确对象类型
+    static ref EXACT_OBJECT_RE: Regex = Regex::new(r"\{\||\|\}").unwrap();
+    // : ?string、<?T> 可空类型
+    static ref MAYBE_TYPE_RE: Regex = Regex::new(r"([:<,]\s*)\?(\s*[\w$({\[])").unwrap();
+    // import typeof X from 'x'
+    static ref IMPORT_TYPEOF_RE: Regex = Regex::new(r"\b(import\s+(?:\{\s*)?)typeof\b").unwrap();
+    // opaque type T = string
+    static ref OPAQUE_TYPE_RE: Regex = Regex::new(r"\bopaque(\s+type\b)").unwrap();
+    // { +a: string }、{ -a?: string } 属性的协变、逆变标记
+    static ref PROPERTY_VARIANCE_RE: Regex =
+        Regex::new(r"(?m)([{,;]|^)(\s*)[+-](\s*[\w$]+\s*\??\s*:)").unwrap();
+    // class A<+T, -U> {}、type T<+V> = ... 泛型参数列表
+    static ref TYPE_PARAMS_RE: Regex =
+        Regex::new(r"(\b(?:class|type|interface)\s+[\w$]+\s*<)([^<>]*)>").unwrap();
+    // 泛型参数列表中的协变、逆变标记
+    static ref PARAM_VARIANCE_RE: Regex = Regex::new(r"(^|,)(\s*)[+-]").unwrap();
+    // function f(x): boolean %checks
+    static ref CHECKS_RE: Regex = Regex::new(r"%checks\b").unwrap();
+}
+
+fn blank(s: &str) -> String {
+    " ".repeat(s.len())
+}
+
+/// 括号前面可以出现表达式的关键字，如 `return (x: any)`
+const EXPRESSION_KEYWORDS: [&str; 12] = [
+    "return", "typeof", "await", "yield", "case", "in", "of", "void", "delete", "throw", "new",
+    "else",
+];
+
+/// 跳过字符串或模板字符串，返回结束引号的位置，模板中的 `${}` 按括号深度跳过
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut depth = 0;
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'$' if quote == b'`' && bytes.get(i + 1) == Some(&b'{') => {
+                depth += 1;
+                i += 1;
+            }
+            b'}' if depth > 0 => depth -= 1,
+            c if c == quote && depth == 0 => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// `start` 处的 `(` 和 `end` 处的 `)` 是否为类型转换，而不是函数或箭头函数的参数
+///
+/// `in_ternary` 表示括号位于三元表达式的 `?` 和 `:` 之间，此时后面的 `:` 属于三元表达式
+fn is_typecast(bytes: &[u8], start: usize, end: usize, in_ternary: bool) -> bool {
+    let before = bytes[..start].trim_ascii_end();
+    let is_ident = |c: &u8| c.is_ascii_alphanumeric() || *c == b'_' || *c == b'$';
+    // foo(x: T)、function foo(x: T) 是参数，return (x: T) 是表达式
+    if before.last().is_some_and(is_ident) {
+        let word_start = before
+            .iter()
+            .rposition(|c| !is_ident(c))
+            .map_or(0, |i| i + 1);
+        let word = std::str::from_utf8(&before[word_start..]).unwrap_or_default();
+        if !EXPRESSION_KEYWORDS.contains(&word) {
+            return false;
+        }
+    }
+    // f<T>(x: T) 是参数，() => (x: T) 是表达式
+    if before.last() == Some(&b'>') && !before.ends_with(b"=>") {
+        return false;
+    }
+    // (x: T) => x、(x: T): R => x 和 function (x: T) { } 是参数
+    let after = bytes[end + 1..].trim_ascii_start();
+    !(after.starts_with(b"=>")
+        || (after.starts_with(b":") && !in_ternary)
+        || after.starts_with(b"{"))
+}
+
+/// 去掉 Flow 类型转换 `(x: any)` 中的类型，得到 `(x     )`
+///
+/// 括号中出现不属于三元表达式的 `:` 且不是参数列表时视为类型转换。
+/// 类型中包含函数类型的转换，如 `(f: () => void)`，会被当作箭头函数保留；
+/// 正则字面量中的括号也会干扰判断，这类文件仍可能解析失败。
+fn strip_typecasts(source: &str) -> String {
+    struct Frame {
+        open: u8,
+        start: usize,
+        ternary: usize,             // 尚未遇到 `:` 的三元表达式 `?` 的个数
+        colon: Option<usize>,       // 类型转换的 `:`
+        arrow: bool,                // `:` 之后出现了 `=>`，是箭头函数的返回类型
+        pending: Vec<Range<usize>>, // 后面紧跟 `:` 的类型转换，如 `((x: any): T)` 中的 `(x: any)`
+    }
+    fn frame(open: u8, start: usize) -> Frame {
+        Frame {
+            open,
+            start,
+            ternary: 0,
+            colon: None,
+            arrow: false,
+            pending: vec![],
+        }
+    }
+
+    let bytes = source.as_bytes();
+    let mut casts: Vec<Range<usize>> = vec![];
+    // 最外层的 frame 用于记录括号外的三元表达式
+    let mut stack: Vec<Frame> = vec![frame(0, 0)];
+    let mut i = 0;
+    while i < bytes.len() {
+        let next = bytes.get(i + 1).copied();
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => i = skip_string(bytes, i),
+            b'/' if next == Some(b'/') => {
+                i = bytes[i..]
+                    .iter()
+                    .position(|c| *c == b'\n')
+                    .map_or(bytes.len(), |n| i + n);
+            }
+            b'/' if next == Some(b'*') => {
+                i = bytes[i + 2..]
+                    .windows(2)
+                    .position(|w| w == b"*/")
+                    .map_or(bytes.len(), |n| i + n + 3);
+            }
+            b'=' if next == Some(b'>') => {
+                let top = stack.last_mut().unwrap();
+                top.arrow |= top.colon.is_some();
+            }
+            open @ (b'(' | b'[' | b'{') => stack.push(frame(open, i)),
+            close @ (b')' | b']' | b'}') if stack.len() > 1 => {
+                let current = stack.pop().unwrap();
+                let parent = stack.last_mut().unwrap();
+                if let Some(colon) = current.colon.filter(|_| close == b')' && !current.arrow) {
+                    if is_typecast(bytes, current.start, i, parent.ternary > 0) {
+                        casts.push(colon..i);
+                        casts.extend(current.pending);
+                    } else if is_typecast(bytes, current.start, i, true) {
+                        parent.pending.push(colon..i);
+                    }
+                }
+            }
+            // a ? b : c，排除 `?.`、`??` 和可选参数 `x?: T`
+            b'?' if !matches!(next, Some(b'.' | b'?' | b':'))
+                && (i == 0 || bytes[i - 1] != b'?') =>
+            {
+                stack.last_mut().unwrap().ternary += 1;
+            }
+            b':' => {
+                let top = stack.last_mut().unwrap();
+                if top.ternary > 0 {
+                    top.ternary -= 1;
+                } else if top.open == b'(' && top.colon.is_none() {
+                    top.colon = Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let mut output = bytes.to_vec();
+    for range in casts {
+        for c in &mut output[range] {
+            if *c != b'\n' && *c != b'\r' {
+                *c = b' ';
+            }
+        }
+    }
+    String::from_utf8(output).unwrap_or_else(|_| source.to_string())
+}
+
+/// 把 Flow 专有的语法去掉，使其可以按 TypeScript 解析
+///
+/// 所有替换都保持长度不变，这样解析得到的位置和原文件一致
+pub fn strip_flow_syntax(source: &str) -> String {
+    let source = EXACT_OBJECT_RE.replace_all(source, |caps: &Captures| {
+        if &caps[0] == "{|" {
+            "{ ".to_string()
+        } else {
+            " }".to_string()
+        }
+    });
+    let source = PROPERTY_VARIANCE_RE.replace_all(&source, "$1$2 $3");
+    let source = TYPE_PARAMS_RE.replace_all(&source, |caps: &Captures| {
+        format!(
+            "{}{}>",
+            &caps[1],
+            PARAM_VARIANCE_RE.replace_all(&caps[2], "$1$2 ")
+        )
+    });
+    let source = MAYBE_TYPE_RE.replace_all(&source, "$1 $2");
+    let source = IMPORT_TYPEOF_RE.replace_all(&source, "${1}type  ");
+    let source = OPAQUE_TYPE_RE.replace_all(&source, |caps: &Captures| {
+        format!("{}{}", blank("opaque"), &caps[1])
+    });
+    let source = CHECKS_RE.replace_all(&source, |caps: &Captures| blank(&caps[0]));
+    strip_typecasts(&source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_flow_syntax_keeps_length() {
+        let source = "// @flow\nimport typeof Foo from './foo';\nopaque type Id = string;\ntype P = {| a: ?string, b: Array<?number> |};\nfunction f(x: mixed): boolean %checks { return !!x; }\n";
+        let stripped = strip_flow_syntax(source);
+        assert_eq!(stripped.len(), source.len());
+        assert_eq!(
+            stripped,
+            "// @flow\nimport type   Foo from './foo';\n       type Id = string;\ntype P = {  a:  string, b: Array< number>  };\nfunction f(x: mixed): boolean         { return !!x; }\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_flow_typecasts() {
+        let source = "const a = (x: any);\nfoo((b: Array<string>), c ? (d: T) : e);\nconst f = (g: T): R => ((g: any): R);\nconst n = ((o: T): R => o);\nfunction h(i: T) { return (i: any); }\nconst j = (k ? l : (m: ?T));\n";
+        let stripped = strip_flow_syntax(source);
+        assert_eq!(stripped.len(), source.len());
+        assert_eq!(
+            stripped,
+            "const a = (x     );\nfoo((b               ), c ? (d   ) : e);\nconst f = (g: T): R => ((g     )   );\nconst n = ((o: T): R => o);\nfunction h(i: T) { return (i     ); }\nconst j = (k ? l : (m    ));\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_flow_variance() {
+        let source = "type P = { +a: string, -b?: number };\nclass A<+T, -U: Object> {\n  +p: T;\n  -q: U;\n}\ntype E = {| +a: ?string |};\nfoo(a, -b, c < -d ? +e : f);\nconst g = { h: -i, j: +k };\n";
+        let stripped = strip_flow_syntax(source);
+        assert_eq!(stripped.len(), source.len());
+        assert_eq!(
+            stripped,
+            "type P = {  a: string,  b?: number };\nclass A< T,  U: Object> {\n   p: T;\n   q: U;\n}\ntype E = {   a:  string  };\nfoo(a, -b, c < -d ? +e : f);\nconst g = { h: -i, j: +k };\n"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use super::consts::DependencyKind;
+use super::sfc::mask_outside;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+
+lazy_static! {
+    static ref COMMENT_RE: Regex = Regex::new(r"(?s)<!--.*?-->").unwrap();
+    static ref SCRIPT_RE: Regex = Regex::new(r"(?is)<script\b([^>]*)>(.*?)</script\s*>").unwrap();
+    static ref LINK_RE: Regex = Regex::new(r"(?i)<link\b([^>]*)>").unwrap();
+    static ref ATTR_RE: Regex =
+        Regex::new(r#"([\w:-]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#).unwrap();
+}
+
+/// HTML 中扫描到的依赖
+pub struct HtmlScan {
+    /// `<script src>`、`<link rel=modulepreload|stylesheet>` 引用的文件，以及所在标签的位置
+    pub references: Vec<(String, DependencyKind, Range<usize>)>,
+    /// 只保留 `<script type="module">` 内联脚本的源码，位置和原文件一致
+    pub source: String,
+}
+
+pub fn is_html(id: &str) -> bool {
+    id.ends_with(".html") || id.ends_with(".htm")
+}
+
+fn parse_attrs(attrs: &str) -> HashMap<String, String> {
+    ATTR_RE
+        .captures_iter(attrs)
+        .map(|captures| {
+            let value = captures
+                .get(2)
+                .or(captures.get(3))
+                .or(captures.get(4))
+                .map_or("", |m| m.as_str());
+            (captures[1].to_lowercase(), value.to_string())
+        })
+        .collect()
+}
+
+/// 把 HTML 中的地址转换为相对于 HTML 文件的请求，外部地址返回 None
+///
+/// 和 Vite 一样，`/src/main.ts` 相对于 HTML 所在的目录
+pub fn to_request(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty()
+        || url.starts_with("//")
+        || url.starts_with('#')
+        || url.contains("://")
+        || url.starts_with("data:")
+    {
+        return None;
+    }
+    if url.starts_with("./") || url.starts_with("../") {
+        Some(url.to_string())
+    } else {
+        Some(format!("./{}", url.trim_start_matches('/')))
+    }
+}
+
+/// 扫描 HTML 中的 script、modulepreload 和 stylesheet 引用
+pub fn scan_html(content: &str) -> HtmlScan {
+    // 注释中的标签不生效，先替换为空白
+    let content = COMMENT_RE.replace_all(content, |captures: &regex::Captures| {
+        mask_outside(&captures[0], &[])
+    });
+
+    let mut references: Vec<(String, DependencyKind, Range<usize>)> = vec![];
+    let mut inline_ranges: Vec<(usize, usize)> = vec![];
+
+    for captures in SCRIPT_RE.captures_iter(&content) {
+        let tag = captures.get(0).unwrap();
+        let attrs = parse_attrs(captures.get(1).map_or("", |m| m.as_str()));
+        if let Some(src) = attrs.get("src") {
+            if let Some(request) = to_request(src) {
+                references.push((request, DependencyKind::HtmlScript, tag.range()));
+            }
+        } else if attrs.get("type").is_some_and(|t| t == "module") {
+            if let Some(body) = captures.get(2) {
+                inline_ranges.push((body.start(), body.end()));
+            }
+        }
+    }
+
+    for captures in LINK_RE.captures_iter(&content) {
+        let tag = captures.get(0).unwrap();
+        let attrs = parse_attrs(captures.get(1).map_or("", |m| m.as_str()));
+        let kind = match attrs.get("rel").map(|rel| rel.to_lowercase()).as_deref() {
+            Some("modulepreload") => DependencyKind::HtmlModulePreload,
+            Some("stylesheet") => DependencyKind::HtmlStylesheet,
+            _ => continue,
+        };
+        if let Some(request) = attrs.get("href").and_then(|href| to_request(href)) {
+            references.push((request, kind, tag.range()));
+        }
+    }
+
+    references.sort_by_key(|(_, _, range)| range.start);
+
+    HtmlScan {
+        references,
+        source: mask_outside(&content, &inline_ranges),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_html() {
+        let content = r#"<!doctype html>
+<html>
+  <head>
+    <link rel="stylesheet" href="/src/style.css">
+    <link rel="modulepreload" href="./vendor.js" />
+    <link rel="icon" href="/favicon.ico">
+    <!-- <script src="./commented.js"></script> -->
+    <script src="https://cdn.example.com/lib.js"></script>
+  </head>
+  <body>
+    <script type="module" src="/src/main.ts"></script>
+    <script type="module">import './inline.js'</script>
+  </body>
+</html>"#;
+        let scan = scan_html(content);
+        assert_eq!(
+            scan.references
+                .iter()
+                .map(|(request, kind, _)| (request.clone(), kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "./src/style.css".to_string(),
+                    DependencyKind::HtmlStylesheet
+                ),
+                ("./vendor.js".to_string(), DependencyKind::HtmlModulePreload),
+                ("./src/main.ts".to_string(), DependencyKind::HtmlScript),
+            ]
+        );
+        assert_eq!(
+            &content[scan.references[2].2.clone()],
+            r#"<script type="module" src="/src/main.ts"></script>"#
+        );
+        assert_eq!(scan.source.len(), content.len());
+        assert_eq!(scan.source.trim(), "import './inline.js'");
+    }
+}
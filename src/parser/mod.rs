@@ -3,4 +3,8 @@ pub mod consts;
 pub mod types;
 pub mod parse_tree_recursive;
 pub mod dependenct_collector;
-pub mod strip_type_only_imports;
+pub mod syntax;
+pub mod flow;
+pub mod sfc;
+pub mod html;
+pub mod css;
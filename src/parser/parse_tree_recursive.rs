@@ -1,20 +1,28 @@
+use super::consts::{DependencyKind, DiagnosticKind, NodeKind};
+use super::css::{is_stylesheet, scan_stylesheet, style_request_path};
 use super::dependenct_collector::DependencyCollector;
-use super::types::{Alias, Dependency, IsModule, ParseOptions};
-use crate::parser::strip_type_only_imports::StripTypeOnlyImports;
-use crate::parser::types::{DependencyTree, ExportSymbol, ImportSymbol, SymbolNode, SymbolTree};
-use crate::utils::resolver::simple_resolver;
+use super::flow::strip_flow_syntax;
+use super::html::{is_html, scan_html};
+use super::sfc::extract_script;
+use super::syntax::{is_flow, module_format, parse_goal, syntax_for};
+use super::types::{Alias, Dependency, IsModule, Location, ParseOptions};
+use crate::parser::types::{
+    DependencyTree, Diagnostic, DiagnosticMessage, ModuleNode, ModuleTree, SymbolNode, SymbolTree,
+};
+use crate::utils::resolver::{simple_resolver, types_resolver};
 use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::fs;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use swc_core::common::{sync::Lrc, FileName, Mark, SourceMap};
-use swc_core::common::{Globals, GLOBALS};
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::{sync::Lrc, BytePos, FileName, Mark, SourceMap, Span, Spanned};
+use swc_core::common::{Globals, SyntaxContext, GLOBALS};
 use swc_core::ecma::ast::{EsVersion, Program};
 use swc_core::ecma::parser::Lexer;
-use swc_core::ecma::parser::{Parser, StringInput, Syntax, TsSyntax};
+use swc_core::ecma::parser::{Parser, StringInput};
 use swc_core::ecma::transforms::base::resolver;
-use swc_core::ecma::transforms::typescript::strip_type;
 use swc_core::ecma::utils::swc_common;
 use swc_core::ecma::visit::{VisitMutWith, VisitWith};
 
@@ -23,11 +31,68 @@ lazy_static! {
         Mutex::new(HashMap::new());
 }
 
+/// 记录无法分析的文件，文件作为没有依赖的节点保留，引用它的依赖仍然有 id
+fn report_diagnostic(
+    output: &Arc<Mutex<DependencyTree>>,
+    diagnostic_output: &Arc<Mutex<Vec<Diagnostic>>>,
+    id: &str,
+    kind: DiagnosticKind,
+    messages: Vec<DiagnosticMessage>,
+) -> Option<String> {
+    {
+        let mut output_lock = output.lock().unwrap();
+        output_lock.insert(id.to_string(), Arc::new(Some(Vec::new())));
+    }
+    {
+        let mut diagnostic_lock = diagnostic_output.lock().unwrap();
+        diagnostic_lock.push(Diagnostic {
+            file: id.to_string(),
+            kind,
+            messages,
+        });
+    }
+    Some(id.to_string())
+}
+
+/// 文本末尾的位置，用于指出第一个不是 UTF-8 的字节
+fn end_location(text: &str) -> Location {
+    let line = text.matches('\n').count() + 1;
+    let column = text.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    Location {
+        start_line: line,
+        start_column: column,
+        end_line: line,
+        end_column: column,
+    }
+}
+
+/// 读取文件并按 UTF-8 解码，失败时返回诊断的类型和信息
+fn read_source(id: &str) -> Result<String, (DiagnosticKind, DiagnosticMessage)> {
+    let bytes = fs::read(id).map_err(|err| {
+        let message = DiagnosticMessage {
+            message: err.to_string(),
+            loc: None,
+        };
+        (DiagnosticKind::Read, message)
+    })?;
+    String::from_utf8(bytes).map_err(|err| {
+        let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+        let message = DiagnosticMessage {
+            message: err.to_string(),
+            loc: std::str::from_utf8(valid).ok().map(end_location),
+        };
+        (DiagnosticKind::Decode, message)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn parse_tree_recursive(
     context: PathBuf,
     path: PathBuf,
     output: Arc<Mutex<DependencyTree>>,
     symbol_output: Arc<Mutex<SymbolTree>>,
+    module_output: Arc<Mutex<ModuleTree>>,
+    diagnostic_output: Arc<Mutex<Vec<Diagnostic>>>,
     cm: Arc<Lrc<SourceMap>>,    // 将 Lrc<SourceMap> 包装在 Arc 中
     options: Arc<ParseOptions>, // 将 ParseOptions 包装在 Arc 中
     alias: Option<Arc<Alias>>,
@@ -60,6 +125,14 @@ pub async fn parse_tree_recursive(
         }
     };
 
+    {
+        let mut module_lock = module_output.lock().unwrap();
+        module_lock.entry(id.clone()).or_insert_with(|| ModuleNode {
+            kind: NodeKind::from_path(&id),
+            format: module_format(&id),
+        });
+    }
+
     // 检查缓存
     {
         let cache = CACHE.lock().unwrap();
@@ -83,7 +156,8 @@ pub async fn parse_tree_recursive(
             } else {
                 format!(".{}", ext.to_string_lossy().to_string())
             };
-            if !options.js.contains(&ext) {
+            // HTML 和样式文件总是会被分析，以支持把 index.html 作为入口
+            if !options.js.contains(&ext) && !is_html(&id) && !is_stylesheet(&id) {
                 let mut output_lock = output.lock().unwrap();
                 output_lock.insert(id.clone(), Arc::new(Some(Vec::new())));
                 return Some(id.clone());
@@ -116,122 +190,209 @@ pub async fn parse_tree_recursive(
             spinner.update_text(text);
         }
     }
-    let file_content = fs::read_to_string(&id).expect("Unable to read file");
+    let mut file_content = match read_source(&id) {
+        Ok(content) => content,
+        Err((kind, message)) => {
+            return report_diagnostic(&output, &diagnostic_output, &id, kind, vec![message]);
+        }
+    };
+    if is_flow(&id, &options) {
+        file_content = strip_flow_syntax(&file_content);
+    }
+    // 单文件组件只解析其中的脚本，按脚本的语言选择语法，如 `App.vue` 按 `App.vue.ts`
+    let mut syntax_id: String = id.clone();
+    let mut extra_dependencies: Vec<(String, DependencyKind, Range<usize>)> = Vec::new();
+    if let Some(script) = extract_script(&id, &file_content) {
+        file_content = script.source;
+        syntax_id = format!("{}.{}", id, script.lang);
+        extra_dependencies = script
+            .srcs
+            .into_iter()
+            .map(|(src, range)| (src, DependencyKind::StaticImport, range))
+            .collect();
+    } else if is_html(&id) {
+        // HTML 只解析 <script type="module"> 内联脚本，其余引用单独扫描
+        let scan = scan_html(&file_content);
+        file_content = scan.source;
+        syntax_id = format!("{}.js", id);
+        extra_dependencies = scan.references;
+    } else if is_stylesheet(&id) {
+        extra_dependencies = scan_stylesheet(&file_content);
+    }
 
     let id_path: PathBuf = Path::new(&id).to_path_buf();
 
     // 使用 swc 解析代码
     let fm: Lrc<swc_common::SourceFile> =
         cm.new_source_file(FileName::Real(id_path.clone()).into(), file_content);
-    let lexer = Lexer::new(
-        Syntax::Typescript(TsSyntax {
-            tsx: true,
-            decorators: false,
-            ..Default::default()
-        }),
-        EsVersion::EsNext,
-        StringInput::from(&*fm),
-        None,
-    );
-
-    let mut parser: Parser<Lexer<'_>> = Parser::new_from(lexer);
-    let program_result = match options.is_module {
-        IsModule::Bool(true) => parser.parse_module().map(Program::Module),
-        IsModule::Bool(false) => parser.parse_script().map(Program::Script),
-        IsModule::Unknown => parser.parse_program(),
-    };
+    let comments = SingleThreadedComments::default();
 
-    let mut program: Program = match program_result {
-        Ok(program) => program,
-        Err(_err) => {
-            // eprintln!("Failed to parse program: {:?}", err);
-            return None;
-        }
-    };
-
-    program = match options.transform {
-        true => match id.ends_with(".tsx") || id.ends_with(".ts") {
-            true => {
-                let program = GLOBALS.set(&Globals::new(), || {
-                    let unresolved_mark = Mark::new();
-                    let top_level_mark = Mark::new();
+    // 样式文件不需要 swc 解析，只扫描其中的引用，源码仍然加入 SourceMap 用于计算位置
+    let syntax = syntax_for(&syntax_id, &options);
+    let mut program: Option<Program> = if is_stylesheet(&id) {
+        None
+    } else {
+        let lexer = Lexer::new(
+            syntax,
+            EsVersion::EsNext,
+            StringInput::from(&*fm),
+            Some(&comments),
+        );
 
-                    program.visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, true));
-                    program.visit_mut_with(&mut StripTypeOnlyImports);
-                    program.visit_mut_with(&mut strip_type());
+        let mut parser: Parser<Lexer<'_>> = Parser::new_from(lexer);
+        let format = module_format(&id);
+        let program_result = match parse_goal(&syntax_id, format.as_ref(), &options) {
+            IsModule::Bool(true) => parser.parse_module().map(Program::Module),
+            IsModule::Bool(false) => parser.parse_script().map(Program::Script),
+            IsModule::Unknown => parser.parse_program(),
+        };
 
-                    program
-                });
-                program
+        match program_result {
+            Ok(program) => Some(program),
+            Err(err) => {
+                let messages = std::iter::once(err)
+                    .chain(parser.take_errors())
+                    .map(|err| DiagnosticMessage {
+                        message: err.kind().msg().to_string(),
+                        loc: Location::from_span(&cm, err.span()),
+                    })
+                    .collect();
+                return report_diagnostic(
+                    &output,
+                    &diagnostic_output,
+                    &id,
+                    DiagnosticKind::Parse,
+                    messages,
+                );
             }
-            false => program,
-        },
-        false => program,
+        }
     };
 
+    // 标记标识符的作用域，只有未绑定到任何声明的 require 才是全局的 require，
+    // 符号依赖也只统计绑定到顶层声明的标识符
+    let (unresolved_ctxt, top_level_ctxt) = GLOBALS.set(&Globals::new(), || {
+        let unresolved_mark = Mark::new();
+        let top_level_mark = Mark::new();
+        if let Some(program) = &mut program {
+            program.visit_mut_with(&mut resolver(
+                unresolved_mark,
+                top_level_mark,
+                syntax.typescript(),
+            ));
+        }
+        (
+            SyntaxContext::empty().apply_mark(unresolved_mark),
+            SyntaxContext::empty().apply_mark(top_level_mark),
+        )
+    });
+
     let new_context: PathBuf = Path::new(&id).parent().unwrap().to_path_buf();
 
-    let dependencies: Vec<Dependency> = Vec::new();
     {
         let mut output_lock = output.lock().unwrap();
         output_lock.insert(id.clone(), Arc::new(Some(Vec::new())));
     }
 
-    let exports: Vec<ExportSymbol> = Vec::new();
-    let imports: Vec<ImportSymbol> = Vec::new();
     // 创建一个依赖收集器
-    let mut collector: DependencyCollector = DependencyCollector {
+    let mut collector: DependencyCollector = DependencyCollector::new(
         id,
-        path: path.clone(),
-        dependencies,
-        collect_symbol: options.symbol,
-        skip_dynamic_imports: options.skip_dynamic_imports,
-        exports,
-        imports,
-        next_import_id: 0,
-        local_symbol_map: HashMap::new(),
-        // local_dynamic_import_ids: vec![]
-        dynamic_import_expr_to_id_map: HashMap::new(),
-    };
+        path.clone(),
+        (*cm).clone(),
+        comments.clone(),
+        unresolved_ctxt,
+        top_level_ctxt,
+    );
+    collector.syntax_id = syntax_id;
+    collector.collect_symbol = options.symbol;
+    collector.skip_dynamic_imports = options.skip_dynamic_imports;
 
     // 遍历 AST
-    program.visit_with(&mut collector);
-
-    {
-        let symbol_node = SymbolNode {
-            exports: collector.exports,
-            imports: collector.imports,
-        };
-        let mut symbol_tree_lock = symbol_output.lock().unwrap();
-        symbol_tree_lock.insert(collector.id.clone(), Arc::new(Some(symbol_node)));
+    for (request, kind, range) in extra_dependencies {
+        let span = Span::new(
+            fm.start_pos + BytePos(range.start as u32),
+            fm.start_pos + BytePos(range.end as u32),
+        );
+        collector.add_dependency(request, kind, span);
+    }
+    if let Some(program) = &program {
+        program.visit_with(&mut collector);
     }
 
     let mut deps: Vec<_> = Vec::new();
     for dep in &collector.dependencies {
-        let path: PathBuf = PathBuf::from(dep.request.clone());
+        let unanalyzable = dep.is_unanalyzable();
+        let types_reference = dep.kind == DependencyKind::TripleSlashTypes;
+        let path: PathBuf = match dep.kind.is_style() {
+            true => style_request_path(&new_context, &dep.request),
+            false => PathBuf::from(dep.request.clone()),
+        };
         let new_context: PathBuf = new_context.clone();
         let output_clone = Arc::clone(&output);
         let symbol_output_clone = Arc::clone(&symbol_output);
+        let module_output_clone = Arc::clone(&module_output);
+        let diagnostic_output_clone = Arc::clone(&diagnostic_output);
         let cm_clone = Arc::clone(&cm);
         let options_clone = Arc::clone(&options);
         let alias_clone = alias.clone();
         let dep_future = async move {
+            if unanalyzable {
+                return None;
+            }
+            // /// <reference types="node" /> 从 @types/node 或包的 types 字段查找
+            let path = match types_reference {
+                true => PathBuf::from(
+                    types_resolver(&new_context.to_string_lossy(), &path.to_string_lossy())
+                        .await
+                        .ok()??,
+                ),
+                false => path,
+            };
             Box::pin(parse_tree_recursive(
                 new_context,
                 path,
                 output_clone,
                 symbol_output_clone,
+                module_output_clone,
+                diagnostic_output_clone,
                 cm_clone,
                 options_clone,
                 alias_clone,
             ))
+            .await
         };
         deps.push(dep_future);
     }
 
     let results = futures::future::join_all(deps).await;
     for (i, dep) in results.into_iter().enumerate() {
-        collector.dependencies[i].id = dep.await;
+        collector.dependencies[i].id = dep;
+    }
+
+    // 符号的来源解析为和依赖相同的文件 id
+    {
+        let request_ids: HashMap<&str, &str> = collector
+            .dependencies
+            .iter()
+            .filter_map(|dep| Some((dep.request.as_str(), dep.id.as_deref()?)))
+            .collect();
+        for import in &mut collector.imports {
+            import.source_id = request_ids
+                .get(import.source.as_str())
+                .map(|id| id.to_string());
+        }
+        for export in &mut collector.exports {
+            export.reexport_id = export
+                .reexport_source
+                .as_deref()
+                .and_then(|request| request_ids.get(request))
+                .map(|id| id.to_string());
+        }
+        let symbol_node = SymbolNode {
+            exports: std::mem::take(&mut collector.exports),
+            imports: std::mem::take(&mut collector.imports),
+        };
+        let mut symbol_tree_lock = symbol_output.lock().unwrap();
+        symbol_tree_lock.insert(collector.id.clone(), Arc::new(Some(symbol_node)));
     }
 
     collector.dependencies.retain(|dep| {
@@ -271,3 +432,91 @@ pub async fn parse_tree_recursive(
     }
     Some(collector.id.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::options::normalize_options;
+    use crate::utils::test_utils::fixture;
+
+    #[test]
+    fn test_read_source_errors() {
+        let missing = fixture("diagnostics/missing.js");
+        let (kind, message) = read_source(&missing.to_string_lossy()).unwrap_err();
+        assert_eq!(kind, DiagnosticKind::Read);
+        assert!(message.loc.is_none());
+
+        let invalid = fixture("diagnostics/invalid-utf8.js");
+        let (kind, message) = read_source(&invalid.to_string_lossy()).unwrap_err();
+        assert_eq!(kind, DiagnosticKind::Decode);
+        // 第一个非 UTF-8 字节在第二行的引号之后
+        assert_eq!(
+            message.loc.map(|loc| loc.to_string()),
+            Some("2:12".to_string())
+        );
+    }
+
+    // parse_tree_recursive 的参数要求用 Arc 包装 SourceMap
+    #[allow(clippy::arc_with_non_send_sync)]
+    #[tokio::test]
+    async fn test_diagnostics_keep_files_in_tree() {
+        let output: Arc<Mutex<DependencyTree>> = Arc::new(Mutex::new(HashMap::new()));
+        let diagnostic_output: Arc<Mutex<Vec<Diagnostic>>> = Arc::new(Mutex::new(Vec::new()));
+        let entry = parse_tree_recursive(
+            fixture("diagnostics"),
+            PathBuf::from("./entry.js"),
+            Arc::clone(&output),
+            Arc::new(Mutex::new(HashMap::new())),
+            Arc::new(Mutex::new(HashMap::new())),
+            Arc::clone(&diagnostic_output),
+            Arc::new(Lrc::new(SourceMap::default())),
+            Arc::new(normalize_options(None)),
+            None,
+        )
+        .await;
+        assert!(entry.is_some());
+
+        let mut diagnostics = diagnostic_output.lock().unwrap().clone();
+        diagnostics.sort_by(|a, b| a.file.cmp(&b.file));
+        let summary: Vec<(&str, DiagnosticKind, Option<String>)> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    Path::new(&diagnostic.file)
+                        .file_name()
+                        .unwrap()
+                        .to_str()
+                        .unwrap(),
+                    diagnostic.kind.clone(),
+                    diagnostic.messages[0]
+                        .loc
+                        .as_ref()
+                        .map(|loc| loc.to_string()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "invalid-utf8.js",
+                    DiagnosticKind::Decode,
+                    Some("2:12".to_string())
+                ),
+                (
+                    "syntax-error.js",
+                    DiagnosticKind::Parse,
+                    Some("2:7".to_string())
+                ),
+            ]
+        );
+
+        // 无法分析的文件仍然是没有依赖的节点，引用它们的依赖有 id
+        let output = output.lock().unwrap();
+        for diagnostic in &diagnostics {
+            assert!(matches!(output[&diagnostic.file].as_ref(), Some(deps) if deps.is_empty()));
+        }
+        let entry_deps = output[&entry.unwrap()].as_ref().clone().unwrap();
+        assert!(entry_deps.iter().all(|dep| dep.id.is_some()));
+    }
+}
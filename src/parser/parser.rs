@@ -1,10 +1,13 @@
 use super::parse_tree_recursive::parse_tree_recursive;
 use super::types::{Alias, ParseOptions};
-use crate::parser::types::{DependencyTree, SymbolTree};
+use crate::parser::types::{DependencyTree, Diagnostic, ModuleTree, SymbolTree};
 use crate::utils::json::strip_jsonc_comments;
 use crate::utils::options::normalize_options;
 use crate::utils::path::join_paths;
-use crate::utils::shorten::{shorten_symbol_tree, shorten_tree};
+use crate::utils::tree::link_symbols;
+use crate::utils::shorten::{
+    shorten_diagnostics, shorten_module_tree, shorten_symbol_tree, shorten_tree,
+};
 use glob::glob;
 use std::collections::HashMap;
 use std::fs;
@@ -15,8 +18,8 @@ use swc_core::common::{sync::Lrc, SourceMap};
 pub async fn parse_dependency_tree(
     entries: &Vec<String>,
     base_options: &ParseOptions,
-) -> (DependencyTree, SymbolTree) {
-    let options: ParseOptions = normalize_options(Some((*base_options).clone()));
+) -> (DependencyTree, SymbolTree, ModuleTree, Vec<Diagnostic>) {
+    let mut options: ParseOptions = normalize_options(Some((*base_options).clone()));
 
     let tsconfig_json = match options.tsconfig.as_ref() {
         Some(tsconfig) => {
@@ -28,13 +31,13 @@ pub async fn parse_dependency_tree(
                         Ok(json) => json,
                         Err(e) => {
                             eprintln!("Failed to parse tsconfig.json: {:?}", e);
-                            return (HashMap::new(), HashMap::new());
+                            return (HashMap::new(), HashMap::new(), HashMap::new(), Vec::new());
                         }
                     }
                 }
                 Err(e) => {
                     eprintln!("Failed to read tsconfig.json: {:?}", e);
-                    return (HashMap::new(), HashMap::new());
+                    return (HashMap::new(), HashMap::new(), HashMap::new(), Vec::new());
                 }
             };
             tsconfig_data
@@ -44,6 +47,14 @@ pub async fn parse_dependency_tree(
         }
     };
 
+    if let Some(experimental_decorators) = tsconfig_json
+        .get("compilerOptions")
+        .and_then(|co| co.get("experimentalDecorators"))
+        .and_then(|ed| ed.as_bool())
+    {
+        options.experimental_decorators = experimental_decorators;
+    }
+
     let current_directory = fs::canonicalize(PathBuf::from(".")).unwrap();
     let root = match tsconfig_json
         .get("compilerOptions")
@@ -90,6 +101,8 @@ pub async fn parse_dependency_tree(
     let cm = Lrc::new(SourceMap::default());
     let output: Arc<Mutex<DependencyTree>> = Arc::new(Mutex::new(HashMap::new()));
     let symbol_output: Arc<Mutex<SymbolTree>> = Arc::new(Mutex::new(HashMap::new()));
+    let module_output: Arc<Mutex<ModuleTree>> = Arc::new(Mutex::new(HashMap::new()));
+    let diagnostic_output: Arc<Mutex<Vec<Diagnostic>>> = Arc::new(Mutex::new(Vec::new()));
 
     // 获取文件列表
     let mut tasks = vec![];
@@ -100,12 +113,16 @@ pub async fn parse_dependency_tree(
                     let path: PathBuf = current_directory.join(filename);
                     let output_clone = Arc::clone(&output);
                     let symbol_output_clone = Arc::clone(&symbol_output);
+                    let module_output_clone = Arc::clone(&module_output);
+                    let diagnostic_output_clone = Arc::clone(&diagnostic_output);
                     let alias_arc = alias.as_ref().map(|a| Arc::new(a.clone()));
                     let task = parse_tree_recursive(
                         current_directory.clone(),
                         path,
                         output_clone,
                         symbol_output_clone,
+                        module_output_clone,
+                        diagnostic_output_clone,
                         Arc::new(cm.clone()),
                         Arc::new(options.clone()),
                         alias_arc,
@@ -121,13 +138,23 @@ pub async fn parse_dependency_tree(
 
     let output_lock = output.lock().unwrap();
     let symbol_lock = symbol_output.lock().unwrap();
+    let module_lock = module_output.lock().unwrap();
+    let diagnostic_lock = diagnostic_output.lock().unwrap();
     let deps_tree = shorten_tree(
         &current_directory.to_string_lossy().to_string(),
         &output_lock,
     );
-    let symbol_tree = shorten_symbol_tree(
+    let symbol_tree = link_symbols(&shorten_symbol_tree(
         &current_directory.to_string_lossy().to_string(),
         &symbol_lock,
+    ));
+    let module_tree = shorten_module_tree(
+        &current_directory.to_string_lossy().to_string(),
+        &module_lock,
+    );
+    let diagnostics = shorten_diagnostics(
+        &current_directory.to_string_lossy().to_string(),
+        &diagnostic_lock,
     );
-    (deps_tree, symbol_tree)
+    (deps_tree, symbol_tree, module_tree, diagnostics)
 }
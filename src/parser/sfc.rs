@@ -0,0 +1,138 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ops::Range;
+use std::path::Path;
+
+lazy_static! {
+    static ref SCRIPT_RE: Regex = Regex::new(r"(?is)<script\b([^>]*)>(.*?)</script\s*>").unwrap();
+    static ref LANG_RE: Regex = Regex::new(r#"(?i)\blang\s*=\s*["']?(\w+)"#).unwrap();
+    static ref SRC_RE: Regex = Regex::new(r#"(?i)\bsrc\s*=\s*["']([^"']+)["']"#).unwrap();
+    static ref ASTRO_FRONTMATTER_RE: Regex =
+        Regex::new(r"(?s)\A\s*---\r?\n(.*?)(?:\r?\n)?---").unwrap();
+}
+
+/// 单文件组件中提取出的脚本
+pub struct SfcScript {
+    /// 只保留脚本内容的源码，其余部分替换为空白，长度和换行与原文件一致
+    pub source: String,
+    /// 脚本语言，`ts`、`tsx` 或 `js`
+    pub lang: String,
+    /// `<script src="...">` 引用的外部脚本，以及所在标签的位置
+    pub srcs: Vec<(String, Range<usize>)>,
+}
+
+fn is_sfc(id: &str) -> bool {
+    matches!(
+        Path::new(id).extension().and_then(|ext| ext.to_str()),
+        Some("vue" | "svelte" | "astro")
+    )
+}
+
+/// 将 `ranges` 之外的字符替换为空格，保留换行，这样 swc 给出的位置可以直接对应到原文件
+pub fn mask_outside(content: &str, ranges: &[(usize, usize)]) -> String {
+    let mut output = String::with_capacity(content.len());
+    for (offset, ch) in content.char_indices() {
+        let inside = ranges
+            .iter()
+            .any(|(start, end)| offset >= *start && offset < *end);
+        if inside || ch == '\n' || ch == '\r' {
+            output.push(ch);
+        } else {
+            output.push_str(&" ".repeat(ch.len_utf8()));
+        }
+    }
+    output
+}
+
+/// 从 Vue、Svelte 的 `<script>`/`<script setup>` 以及 Astro 的 frontmatter 中提取脚本
+///
+/// 不是单文件组件时返回 None
+pub fn extract_script(id: &str, content: &str) -> Option<SfcScript> {
+    if !is_sfc(id) {
+        return None;
+    }
+
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    let mut langs: Vec<String> = vec![];
+    let mut srcs: Vec<(String, Range<usize>)> = vec![];
+
+    if id.ends_with(".astro") {
+        if let Some(frontmatter) = ASTRO_FRONTMATTER_RE
+            .captures(content)
+            .and_then(|c| c.get(1))
+        {
+            ranges.push((frontmatter.start(), frontmatter.end()));
+            // Astro 的 frontmatter 总是 TypeScript
+            langs.push("ts".to_string());
+        }
+    }
+
+    for captures in SCRIPT_RE.captures_iter(content) {
+        let attrs = captures.get(1).map_or("", |m| m.as_str());
+        // Astro 中 is:inline 的脚本不会被打包
+        if attrs.contains("is:inline") {
+            continue;
+        }
+        if let Some(src) = SRC_RE.captures(attrs) {
+            srcs.push((src[1].to_string(), captures.get(0).unwrap().range()));
+        }
+        if let Some(lang) = LANG_RE.captures(attrs) {
+            langs.push(lang[1].to_lowercase());
+        }
+        if let Some(body) = captures.get(2) {
+            ranges.push((body.start(), body.end()));
+        }
+    }
+
+    let lang = if langs.iter().any(|lang| lang == "tsx") {
+        "tsx"
+    } else if langs.iter().any(|lang| lang == "ts") {
+        "ts"
+    } else {
+        "js"
+    };
+
+    Some(SfcScript {
+        source: mask_outside(content, &ranges),
+        lang: lang.to_string(),
+        srcs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_vue_script_setup() {
+        let content = "<template>\n  <Foo />\n</template>\n<script setup lang=\"ts\">\nimport Foo from './Foo.vue'\n</script>\n";
+        let script = extract_script("App.vue", content).unwrap();
+        assert_eq!(script.lang, "ts");
+        assert_eq!(script.source.len(), content.len());
+        assert_eq!(
+            script.source,
+            "          \n         \n           \n                        \nimport Foo from './Foo.vue'\n         \n"
+        );
+    }
+
+    #[test]
+    fn test_extract_astro_frontmatter() {
+        let content = "---\nimport Layout from '../layouts/Layout.astro';\n---\n<Layout />\n<script src=\"./client.ts\"></script>\n";
+        let script = extract_script("index.astro", content).unwrap();
+        assert_eq!(script.lang, "ts");
+        assert_eq!(script.srcs.len(), 1);
+        assert_eq!(script.srcs[0].0, "./client.ts");
+        assert_eq!(
+            &content[script.srcs[0].1.clone()],
+            "<script src=\"./client.ts\"></script>"
+        );
+        assert!(script
+            .source
+            .starts_with("   \nimport Layout from '../layouts/Layout.astro';\n   \n"));
+    }
+
+    #[test]
+    fn test_extract_script_skips_other_files() {
+        assert!(extract_script("index.ts", "import './a'").is_none());
+    }
+}
@@ -0,0 +1,224 @@
+use super::consts::ModuleFormat;
+use super::types::{IsModule, ParseOptions};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use swc_core::ecma::parser::{EsSyntax, Syntax, TsSyntax};
+
+lazy_static! {
+    // 目录 -> 最近的 package.json 中的 type
+    static ref PACKAGE_TYPE_CACHE: Mutex<HashMap<PathBuf, Option<String>>> =
+        Mutex::new(HashMap::new());
+}
+
+fn extension(id: &str) -> String {
+    Path::new(id)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 开启 `--flow` 后，js 类文件都按 Flow 处理
+pub fn is_flow(id: &str, options: &ParseOptions) -> bool {
+    options.flow
+        && matches!(
+            extension(id).as_str(),
+            "js" | "jsx" | "mjs" | "cjs" | "flow"
+        )
+}
+
+/// 根据文件后缀和配置选择 swc 的语法
+///
+/// - `.ts` 不开启 tsx，否则 `<T>x` 这种类型断言会解析失败
+/// - js 类文件使用 `EsSyntax` 并开启 JSX
+/// - tsconfig 开启 `experimentalDecorators` 时使用 legacy 装饰器，否则使用 stage 3 装饰器
+/// - Flow 文件在去掉 Flow 专有语法后按 tsx 解析
+pub fn syntax_for(id: &str, options: &ParseOptions) -> Syntax {
+    if is_flow(id, options) {
+        return Syntax::Typescript(TsSyntax {
+            tsx: true,
+            decorators: true,
+            ..Default::default()
+        });
+    }
+
+    match extension(id).as_str() {
+        "ts" | "mts" | "cts" => Syntax::Typescript(TsSyntax {
+            tsx: false,
+            decorators: true,
+            dts: id.ends_with(".d.ts") || id.ends_with(".d.mts") || id.ends_with(".d.cts"),
+            ..Default::default()
+        }),
+        "js" | "jsx" | "mjs" | "cjs" => Syntax::Es(EsSyntax {
+            jsx: true,
+            decorators: true,
+            decorators_before_export: options.experimental_decorators,
+            auto_accessors: !options.experimental_decorators,
+            import_attributes: true,
+            explicit_resource_management: true,
+            // CommonJS 允许顶层 return
+            allow_return_outside_function: module_format(id) == Some(ModuleFormat::CommonJS),
+            ..Default::default()
+        }),
+        _ => Syntax::Typescript(TsSyntax {
+            tsx: true,
+            decorators: true,
+            ..Default::default()
+        }),
+    }
+}
+
+/// 最近的 package.json 中的 `type`，找到的 package.json 没有 `type` 时返回 None
+fn package_type(dir: &Path) -> Option<String> {
+    if let Some(cached) = PACKAGE_TYPE_CACHE.lock().unwrap().get(dir) {
+        return cached.clone();
+    }
+
+    let package_path = dir.join("package.json");
+    let package_type = if package_path.is_file() {
+        fs::read_to_string(&package_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|json| json.get("type")?.as_str().map(String::from))
+    } else {
+        dir.parent().and_then(package_type)
+    };
+
+    PACKAGE_TYPE_CACHE
+        .lock()
+        .unwrap()
+        .insert(dir.to_path_buf(), package_type.clone());
+    package_type
+}
+
+/// 判断文件的模块格式，和 Node.js 一样 `.mjs`/`.cjs` 优先于 package.json 的 `type`
+///
+/// package.json 没有声明 `type` 时无法确定，交给 swc 根据语法判断
+pub fn module_format(id: &str) -> Option<ModuleFormat> {
+    match extension(id).as_str() {
+        "mjs" | "mts" => Some(ModuleFormat::Esm),
+        "cjs" | "cts" => Some(ModuleFormat::CommonJS),
+        // 单文件组件和 HTML 中的 <script type="module"> 总是 ESM
+        "vue" | "svelte" | "astro" | "html" | "htm" => Some(ModuleFormat::Esm),
+        "js" | "jsx" | "ts" | "tsx" => match package_type(Path::new(id).parent()?).as_deref() {
+            Some("module") => Some(ModuleFormat::Esm),
+            Some("commonjs") => Some(ModuleFormat::CommonJS),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 选择 `parse_module` 还是 `parse_script`
+///
+/// TypeScript 中的 CommonJS 文件仍然可以使用 import 语法，所以只有 js 类文件按 script 解析
+pub fn parse_goal(
+    syntax_id: &str,
+    format: Option<&ModuleFormat>,
+    options: &ParseOptions,
+) -> IsModule {
+    if let IsModule::Bool(is_module) = options.is_module {
+        return IsModule::Bool(is_module);
+    }
+    match format {
+        Some(ModuleFormat::Esm) => IsModule::Bool(true),
+        Some(ModuleFormat::CommonJS)
+            if matches!(extension(syntax_id).as_str(), "js" | "jsx" | "cjs") =>
+        {
+            IsModule::Bool(false)
+        }
+        _ => IsModule::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::options::normalize_options;
+    use crate::utils::test_utils::fixture;
+
+    #[test]
+    fn test_module_format() {
+        assert_eq!(
+            module_format(&fixture("module-format/esm/a.js").to_string_lossy()),
+            Some(ModuleFormat::Esm)
+        );
+        assert_eq!(
+            module_format(&fixture("module-format/esm/nested/b.ts").to_string_lossy()),
+            Some(ModuleFormat::Esm)
+        );
+        assert_eq!(
+            module_format(&fixture("module-format/esm/c.cjs").to_string_lossy()),
+            Some(ModuleFormat::CommonJS)
+        );
+        assert_eq!(
+            module_format(&fixture("module-format/esm/legacy/d.js").to_string_lossy()),
+            Some(ModuleFormat::CommonJS)
+        );
+        assert_eq!(
+            module_format(&fixture("module-format/untyped/e.js").to_string_lossy()),
+            None
+        );
+        assert_eq!(
+            module_format(&fixture("module-format/untyped/f.mjs").to_string_lossy()),
+            Some(ModuleFormat::Esm)
+        );
+    }
+
+    #[test]
+    fn test_syntax_for() {
+        let mut options = normalize_options(None);
+        assert!(matches!(
+            syntax_for("a.ts", &options),
+            Syntax::Typescript(TsSyntax {
+                tsx: false,
+                dts: false,
+                ..
+            })
+        ));
+        assert!(matches!(
+            syntax_for("a.d.ts", &options),
+            Syntax::Typescript(TsSyntax {
+                tsx: false,
+                dts: true,
+                ..
+            })
+        ));
+        assert!(matches!(
+            syntax_for("a.tsx", &options),
+            Syntax::Typescript(TsSyntax { tsx: true, .. })
+        ));
+        assert!(matches!(
+            syntax_for("a.js", &options),
+            Syntax::Es(EsSyntax {
+                jsx: true,
+                decorators_before_export: false,
+                auto_accessors: true,
+                ..
+            })
+        ));
+
+        // tsconfig 开启 experimentalDecorators
+        options.experimental_decorators = true;
+        assert!(matches!(
+            syntax_for("a.js", &options),
+            Syntax::Es(EsSyntax {
+                decorators_before_export: true,
+                auto_accessors: false,
+                ..
+            })
+        ));
+
+        options.flow = true;
+        assert!(matches!(
+            syntax_for("a.js", &options),
+            Syntax::Typescript(TsSyntax { tsx: true, .. })
+        ));
+        assert!(matches!(
+            syntax_for("a.ts", &options),
+            Syntax::Typescript(TsSyntax { tsx: false, .. })
+        ));
+    }
+}
@@ -1,13 +1,14 @@
-use crate::parser::consts::DependencyKind;
+use crate::parser::consts::{DependencyKind, DiagnosticKind, ModuleFormat, NodeKind, SymbolKind};
 use regex::Regex;
 use serde::{self, Serializer};
 use spinoff::Spinner;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     path::PathBuf,
     sync::{Arc, Mutex},
 };
+use swc_core::common::{SourceMap, Span};
 
 fn serialize_regex<S>(regex: &Regex, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -55,9 +56,10 @@ pub struct ParseOptions {
     pub progress: Option<Progress>,
 
     pub symbol: bool,
-    pub transform: bool,
     pub skip_dynamic_imports: bool,
-    pub is_module: IsModule, // 是否是 ESM 模块
+    pub flow: bool,                    // js 类文件按 Flow 解析
+    pub experimental_decorators: bool, // 来自 tsconfig 的 experimentalDecorators
+    pub is_module: IsModule,           // 是否是 ESM 模块
 }
 
 #[derive(Debug, serde::Serialize, Clone)]
@@ -66,14 +68,94 @@ pub struct Dependency {
     pub request: String,
     pub kind: DependencyKind,
     pub id: Option<String>,
+    pub expression: Option<String>, // 部分静态的动态导入的原始表达式
+    pub context_kind: Option<DependencyKind>, // 展开为 DynamicContext 的调用，import() 或 require()
+    pub type_only: bool,            // 只在类型位置使用，编译后会被移除
+    pub in_try: bool,               // 在 try 块中，如 `try { require('optional') } catch {}`
+    pub conditional: bool,          // 在条件分支中，如 `if (dev) require('./dev-only')`
+    pub in_function: bool,          // 在不会在加载时执行的函数体中
+    pub eager: bool,                // 在模块求值时就会加载，可能观察到未初始化完成的模块
+    pub ignored: Option<String>,    // 被 dpdm-ignore 注释忽略的原因，不参与循环依赖检测和警告
+    pub metadata: BTreeMap<String, String>, // magic comment 和导入属性，如 webpackChunkName、with.type
+    pub loc: Option<Location>,              // 在 issuer 中的位置
+}
+
+/// 源码中的位置，行和列都从 1 开始
+#[derive(Debug, serde::Serialize, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Location {
+    /// 通过 SourceMap 把 span 转换为行列号
+    pub fn from_span(source_map: &SourceMap, span: Span) -> Option<Location> {
+        if span.is_dummy() {
+            return None;
+        }
+        let start = source_map.lookup_char_pos(span.lo);
+        let end = source_map.lookup_char_pos(span.hi);
+        Some(Location {
+            start_line: start.line,
+            start_column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+        })
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start_line, self.start_column)
+    }
+}
+
+impl Dependency {
+    /// 无法静态展开的动态导入，如 `import(name)`，只用于产生警告
+    pub fn is_unanalyzable(&self) -> bool {
+        self.kind == DependencyKind::DynamicContext
+            && self.expression.as_deref() == Some(self.request.as_str())
+    }
+
+    /// 是否为异步加载的依赖，包括 `import()` 展开的 DynamicContext 依赖
+    pub fn is_dynamic(&self) -> bool {
+        self.kind.is_dynamic()
+            || self
+                .context_kind
+                .as_ref()
+                .is_some_and(DependencyKind::is_dynamic)
+    }
+
+    /// 可选的依赖，找不到时只降级为提示
+    pub fn is_optional(&self) -> bool {
+        self.in_try || self.conditional
+    }
+
+    /// 带有 `/* @vite-ignore */` 的依赖，Vite 不会分析它，也不产生警告
+    pub fn is_vite_ignored(&self) -> bool {
+        self.metadata.contains_key("@vite-ignore")
+    }
+
+    /// issuer 加上依赖所在的行列，如 `src/a.ts:3:1`，方便在编辑器中跳转
+    pub fn issuer_location(&self) -> String {
+        match &self.loc {
+            Some(loc) => format!("{}:{}", self.issuer, loc),
+            None => self.issuer.clone(),
+        }
+    }
 }
 
 #[derive(Debug, serde::Serialize, Clone)]
 pub struct ImportSymbol {
     pub id: usize,
-    pub local: String,    // 本地变量名
-    pub imported: String, // 从外部导入的符号名（对于 default/namespace 特别标记）
-    pub source: String,   // 来源模块
+    pub local: String,             // 本地变量名
+    pub imported: String,          // 从外部导入的符号名（对于 default/namespace 特别标记）
+    pub source: String,            // 来源模块
+    pub type_only: bool,           // 只作为类型使用，如 import type 或只在类型位置引用
+    pub source_id: Option<String>, // 来源模块的文件 id，和依赖树的 key 一致，无法解析时为空
+    pub origin: Option<SymbolRef>, // 沿 re-export 和 export * 找到的原始导出
 }
 
 #[derive(Debug, serde::Serialize, Clone)]
@@ -81,7 +163,16 @@ pub struct ExportSymbol {
     pub local: String,                   // 本地符号名
     pub exported: String,                // 导出的符号名
     pub reexport_source: Option<String>, // 如果是 re-export，标记源模块
+    pub reexport_id: Option<String>,     // re-export 源模块的文件 id
     pub depends_on: Vec<usize>,
+    pub kind: SymbolKind, // 值、类型、枚举、命名空间、类、函数或变量
+}
+
+/// 指向某个文件中的导出符号
+#[derive(Debug, serde::Serialize, Clone, PartialEq)]
+pub struct SymbolRef {
+    pub file: String,     // 文件 id
+    pub exported: String, // 导出的符号名
 }
 
 pub type DependencyTree = HashMap<String, Arc<Option<Vec<Dependency>>>>;
@@ -93,6 +184,27 @@ pub struct SymbolNode {
 }
 pub type SymbolTree = HashMap<String, Arc<Option<SymbolNode>>>;
 
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct ModuleNode {
+    pub kind: NodeKind,
+    pub format: Option<ModuleFormat>, // ESM 或 CommonJS，无法确定时为空
+}
+pub type ModuleTree = HashMap<String, ModuleNode>;
+
+/// 读取、解码或解析失败的文件，文件本身仍作为没有依赖的节点保留在依赖树中
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct Diagnostic {
+    pub file: String,
+    pub kind: DiagnosticKind,
+    pub messages: Vec<DiagnosticMessage>,
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct DiagnosticMessage {
+    pub message: String,
+    pub loc: Option<Location>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Alias {
     pub root: PathBuf,
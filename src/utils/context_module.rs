@@ -0,0 +1,205 @@
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::path::{join_paths, relative_request};
+
+/// 递归收集目录下的文件，跳过 node_modules
+fn walk_dir(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive && entry.file_name() != "node_modules" {
+                walk_dir(&path, recursive, files);
+            }
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+}
+
+/// 将 JS 正则字面量转换为 Rust 正则，不支持的语法返回 None
+pub fn js_regex(exp: &str, flags: &str) -> Option<Regex> {
+    let prefix = if flags.contains('i') { "(?i)" } else { "" };
+    Regex::new(&format!("{}{}", prefix, exp)).ok()
+}
+
+/// 展开 webpack 的 `require.context(directory, recursive, regexp)`
+///
+/// 返回相对于 `base_dir`（issuer 所在目录）的请求路径，正则匹配的是 webpack 的 key，
+/// 即 `./sub/file.ext` 形式的相对路径。
+pub fn expand_require_context(
+    base_dir: &Path,
+    directory: &str,
+    recursive: bool,
+    regexp: &Regex,
+) -> Vec<String> {
+    let root = join_paths(&[base_dir, Path::new(directory)]);
+    let mut files = Vec::new();
+    walk_dir(&root, recursive, &mut files);
+    files.sort();
+
+    files
+        .into_iter()
+        .filter(|file| regexp.is_match(&relative_request(&root, file)))
+        .map(|file| relative_request(base_dir, file))
+        .collect()
+}
+
+/// 展开 Vite 的 `import.meta.glob(patterns)`
+///
+/// 只支持相对路径的 pattern，`!` 开头的 pattern 用于排除，和 Vite 一样默认忽略 node_modules。
+pub fn expand_import_glob(base_dir: &Path, patterns: &[String]) -> Vec<String> {
+    let mut included: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut excluded: BTreeSet<PathBuf> = BTreeSet::new();
+
+    for pattern in patterns {
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern.as_str()),
+        };
+        if !pattern.starts_with('.') {
+            continue;
+        }
+
+        let full_pattern = join_paths(&[base_dir, Path::new(pattern)]);
+        let matches = match glob::glob(&full_pattern.to_string_lossy()) {
+            Ok(paths) => paths.filter_map(Result::ok),
+            Err(_) => continue,
+        };
+        for path in matches {
+            if !path.is_file() || path.components().any(|c| c.as_os_str() == "node_modules") {
+                continue;
+            }
+            if negated {
+                excluded.insert(path);
+            } else {
+                included.insert(path);
+            }
+        }
+    }
+
+    included
+        .difference(&excluded)
+        .map(|file| relative_request(base_dir, file))
+        .collect()
+}
+
+/// 展开部分静态的动态导入，如 `./locales/${lang}.json` 或 `'./plugins/' + name`
+///
+/// `segments` 为被动态部分分隔开的静态片段，和 webpack 一样从第一个片段取目录，
+/// 其余部分转换为正则。`issuer` 自身不会出现在结果中。无法确定目录时返回 None。
+pub fn expand_dynamic_context(
+    base_dir: &Path,
+    segments: &[String],
+    issuer: &Path,
+) -> Option<Vec<String>> {
+    let prefix = segments.first()?;
+    if !prefix.starts_with("./") && !prefix.starts_with("../") {
+        return None;
+    }
+    let index = prefix.rfind('/')?;
+    let (directory, rest) = (&prefix[..=index], &prefix[index + 1..]);
+
+    let mut pattern = format!(r"^\./{}", regex::escape(rest));
+    for segment in &segments[1..] {
+        pattern.push_str(".*");
+        pattern.push_str(&regex::escape(segment));
+    }
+    pattern.push('$');
+
+    let regexp = Regex::new(&pattern).ok()?;
+    let issuer = relative_request(base_dir, issuer);
+    Some(
+        expand_require_context(base_dir, directory, true, &regexp)
+            .into_iter()
+            .filter(|request| *request != issuer)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::fixture;
+
+    #[test]
+    fn test_expand_require_context() {
+        let regexp = js_regex(r"\.tsx$", "").unwrap();
+        assert_eq!(
+            expand_require_context(&fixture("context-module"), "./pages", true, &regexp),
+            vec![
+                "./pages/about.tsx",
+                "./pages/home.tsx",
+                "./pages/nested/detail.tsx"
+            ]
+        );
+        assert_eq!(
+            expand_require_context(&fixture("context-module"), "./pages", false, &regexp),
+            vec!["./pages/about.tsx", "./pages/home.tsx"]
+        );
+    }
+
+    #[test]
+    fn test_expand_dynamic_context() {
+        let issuer = fixture("context-module/index.ts");
+        assert_eq!(
+            expand_dynamic_context(
+                &fixture("context-module"),
+                &["./pages/".to_string(), ".tsx".to_string()],
+                &issuer
+            ),
+            Some(vec![
+                "./pages/about.tsx".to_string(),
+                "./pages/home.tsx".to_string(),
+                "./pages/nested/detail.tsx".to_string()
+            ])
+        );
+        assert_eq!(
+            expand_dynamic_context(
+                &fixture("context-module"),
+                &["./modules/b".to_string(), "".to_string()],
+                &issuer
+            ),
+            Some(vec![
+                "./modules/b.spec.ts".to_string(),
+                "./modules/b.ts".to_string()
+            ])
+        );
+        assert_eq!(
+            expand_dynamic_context(
+                &fixture("context-module"),
+                &["./modules/b".to_string(), "".to_string()],
+                &fixture("context-module/modules/b.ts")
+            ),
+            Some(vec!["./modules/b.spec.ts".to_string()])
+        );
+        assert_eq!(
+            expand_dynamic_context(
+                &fixture("context-module"),
+                &["".to_string(), "/x".to_string()],
+                &issuer
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_expand_import_glob() {
+        assert_eq!(
+            expand_import_glob(
+                &fixture("context-module/pages"),
+                &[
+                    "../modules/*.ts".to_string(),
+                    "!../modules/*.spec.ts".to_string()
+                ]
+            ),
+            vec!["../modules/a.ts", "../modules/b.ts"]
+        );
+    }
+}
@@ -6,3 +6,6 @@ pub mod tree;
 pub mod alias;
 pub mod pretty;
 pub mod json;
+pub mod context_module;
+#[cfg(test)]
+pub mod test_utils;
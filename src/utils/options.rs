@@ -28,33 +28,27 @@ pub fn normalize_options(options: Option<ParseOptions>) -> ParseOptions {
         include: Regex::new(".*").unwrap(),
         exclude: Regex::new("node_modules").unwrap(),
         tsconfig: None,
-        transform: false,
         skip_dynamic_imports: false,
+        flow: false,
+        experimental_decorators: false,
         progress: None,
         is_module: IsModule::Unknown,
     };
 
-    // TODO: 目前打开之后无法处理混合模块
-    // let package_path = PathBuf::from(&new_options.context).join("package.json");
-    // if package_path.is_file() {
-    //     let package_json = fs::read_to_string(package_path).unwrap();
-    //     let package_json: serde_json::Value = serde_json::from_str(&package_json).unwrap();
-    //     if let Some(package_type) = package_json.get("type") {
-    //         new_options.is_module = IsModule::Bool(package_type == "module");
-    //     }
-    // }
+    // 模块格式按文件判断，见 parser::syntax::module_format
 
     if let Some(opts) = options {
         new_options.extensions.extend(opts.extensions);
         new_options.context = opts.context;
         new_options.tsconfig = opts.tsconfig;
-        new_options.transform = opts.transform;
         new_options.skip_dynamic_imports = opts.skip_dynamic_imports;
         new_options.progress = opts.progress;
         new_options.exclude = opts.exclude;
         new_options.include = opts.include;
         new_options.js = opts.js;
         new_options.symbol = opts.symbol;
+        new_options.flow = opts.flow;
+        new_options.experimental_decorators = opts.experimental_decorators;
     }
 
     if !new_options.extensions.contains(&"".to_string()) {
@@ -1,4 +1,4 @@
-use std::path::{Component, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 pub fn join_paths<P: AsRef<std::path::Path>>(paths: &[P]) -> PathBuf {
     let mut result = PathBuf::new();
@@ -42,6 +42,31 @@ pub fn join_paths<P: AsRef<std::path::Path>>(paths: &[P]) -> PathBuf {
     normalized
 }
 
+/// 计算 `to` 相对于目录 `from` 的请求路径，总是以 `./` 或 `../` 开头
+pub fn relative_request<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> String {
+    let from: Vec<Component> = from.as_ref().components().collect();
+    let to: Vec<Component> = to.as_ref().components().collect();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+
+    let joined = parts.join("/");
+    if joined.starts_with("..") {
+        joined
+    } else {
+        format!("./{}", joined)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,4 +122,16 @@ mod tests {
         let full_path = join_paths(&paths);
         assert_eq!(full_path, PathBuf::from("/documents"));
     }
+
+    #[test]
+    fn test_relative_request() {
+        assert_eq!(
+            relative_request("/users/john", "/users/john/pages/a.ts"),
+            "./pages/a.ts"
+        );
+        assert_eq!(
+            relative_request("/users/john/src", "/users/modules/b.ts"),
+            "../../modules/b.ts"
+        );
+    }
 }
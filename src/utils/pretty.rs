@@ -3,7 +3,30 @@ use colored::Colorize;
 use crate::{node_resolve::node_builtins::BUILTINS, parser::types::DependencyTree};
 use std::collections::HashMap;
 
-pub fn pretty_circular(circulars: &[Vec<String>], prefix: &str) -> String {
+use super::tree::CircularOptions;
+
+/// 循环中 `from` 引用 `to` 的位置，只考虑循环依赖检测时经过的依赖
+fn edge_location(
+    tree: &DependencyTree,
+    from: &str,
+    to: &str,
+    options: &CircularOptions,
+) -> Option<String> {
+    tree.get(from)?
+        .as_ref()
+        .as_ref()?
+        .iter()
+        .find(|dep| dep.id.as_deref() == Some(to) && options.follows(dep))
+        .and_then(|dep| dep.loc.as_ref())
+        .map(|loc| format!(":{}", loc))
+}
+
+pub fn pretty_circular(
+    circulars: &[Vec<String>],
+    tree: &DependencyTree,
+    options: &CircularOptions,
+    prefix: &str,
+) -> String {
     let digits = (circulars.len() as f64).log10().ceil() as usize;
     circulars
         .iter()
@@ -15,7 +38,17 @@ pub fn pretty_circular(circulars: &[Vec<String>], prefix: &str) -> String {
                 format!("{:0>width$}", index + 1, width = digits).color("gray"),
                 ") ".color("gray"),
                 line.iter()
-                    .map(|item| item.red().to_string())
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let next = &line[(i + 1) % line.len()];
+                        format!(
+                            "{}{}",
+                            item.red(),
+                            edge_location(tree, item, next, options)
+                                .unwrap_or_default()
+                                .color("gray")
+                        )
+                    })
                     .collect::<Vec<_>>()
                     .join(&" -> ".color("gray").to_string())
             )
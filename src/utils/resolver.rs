@@ -110,3 +110,52 @@ pub async fn simple_resolver(
 
     Ok(None)
 }
+
+/// 解析 `/// <reference types="..." />`
+///
+/// 依次查找 `@types/<name>` 和 `<name>` 自身，入口为 package.json 的 `types`/`typings`，
+/// 没有时使用 `index.d.ts`。`vite/client` 这样带子路径的引用解析为包目录下的 `client.d.ts`。
+pub async fn types_resolver(
+    context: &str,
+    name: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let segments = if name.starts_with('@') { 2 } else { 1 };
+    let parts: Vec<&str> = name.splitn(segments + 1, '/').collect();
+    if parts.len() < segments {
+        return Ok(None);
+    }
+    let package = parts[..segments].join("/");
+    let subpath = parts.get(segments).copied().unwrap_or_default();
+
+    // @scope/pkg 的类型包为 @types/scope__pkg
+    let types_package = format!(
+        "@types/{}",
+        package.trim_start_matches('@').replace('/', "__")
+    );
+    let extensions = vec![String::new(), ".d.ts".to_string()];
+    for package in [types_package, package] {
+        let pkg_path = Path::new(&package)
+            .join("package.json")
+            .to_string_lossy()
+            .into_owned();
+        let Ok(resolved_path) = resolve_from(&pkg_path, PathBuf::from(context)) else {
+            continue;
+        };
+        let pkg_json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&resolved_path)?)?;
+        let entry = match subpath {
+            "" => pkg_json
+                .get("types")
+                .or_else(|| pkg_json.get("typings"))
+                .and_then(|entry| entry.as_str())
+                .unwrap_or("index"),
+            subpath => subpath,
+        };
+        let id = join_paths(&[resolved_path.parent().unwrap(), Path::new(entry)]);
+        if let Some(id) = append_suffix(&id.to_string_lossy(), &extensions).await? {
+            return Ok(Some(id));
+        }
+    }
+
+    Ok(None)
+}
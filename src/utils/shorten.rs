@@ -1,6 +1,9 @@
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
-use crate::parser::types::{Dependency, DependencyTree, SymbolTree};
+use crate::parser::types::{
+    Dependency, DependencyTree, Diagnostic, ExportSymbol, ImportSymbol, ModuleTree, SymbolNode,
+    SymbolTree,
+};
 
 pub fn shorten_tree(context: &String, tree: &DependencyTree) -> DependencyTree {
     let mut output: DependencyTree = HashMap::new();
@@ -22,8 +25,6 @@ pub fn shorten_tree(context: &String, tree: &DependencyTree) -> DependencyTree {
                     deps.iter()
                         .map(|item| Dependency {
                             issuer: short_key.clone(),
-                            request: item.request.clone(),
-                            kind: item.kind.clone(),
                             id: item.id.as_ref().map(|id| {
                                 Path::new(id)
                                     .strip_prefix(&context)
@@ -32,6 +33,7 @@ pub fn shorten_tree(context: &String, tree: &DependencyTree) -> DependencyTree {
                                     .unwrap()
                                     .to_string()
                             }),
+                            ..item.clone()
                         })
                         .collect::<Vec<Dependency>>()
                 })
@@ -63,7 +65,48 @@ pub fn shorten_symbol_tree(context: &String, tree: &SymbolTree) -> SymbolTree {
             .to_str()
             .unwrap()
             .to_string();
-        output.insert(short_key.clone(), symbol_node.clone());
+        let symbol_node = symbol_node.as_ref().as_ref().map(|node| SymbolNode {
+            imports: node
+                .imports
+                .iter()
+                .map(|item| ImportSymbol {
+                    source_id: item.source_id.as_ref().map(|id| shorten_path(id, context)),
+                    ..item.clone()
+                })
+                .collect(),
+            exports: node
+                .exports
+                .iter()
+                .map(|item| ExportSymbol {
+                    reexport_id: item
+                        .reexport_id
+                        .as_ref()
+                        .map(|id| shorten_path(id, context)),
+                    ..item.clone()
+                })
+                .collect(),
+        });
+        output.insert(short_key.clone(), Arc::new(symbol_node));
     }
     output
 }
+
+pub fn shorten_module_tree(context: &String, tree: &ModuleTree) -> ModuleTree {
+    tree.iter()
+        .filter(|(key, _)| !key.contains("node_modules"))
+        .map(|(key, node)| (shorten_path(key, context), node.clone()))
+        .collect()
+}
+
+pub fn shorten_diagnostics(context: &String, diagnostics: &[Diagnostic]) -> Vec<Diagnostic> {
+    let mut output: Vec<Diagnostic> = diagnostics
+        .iter()
+        .filter(|item| !item.file.contains("node_modules"))
+        .map(|item| Diagnostic {
+            file: shorten_path(&item.file, context),
+            ..item.clone()
+        })
+        .collect();
+    output.sort_by(|a, b| a.file.cmp(&b.file));
+    output
+}
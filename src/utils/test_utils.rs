@@ -0,0 +1,7 @@
+use std::env;
+use std::path::PathBuf;
+
+/// 测试用的 `fixtures` 目录下的路径，如 `fixture("css/reset.css")`
+pub fn fixture(part: &str) -> PathBuf {
+    env::current_dir().unwrap().join("fixtures").join(part)
+}
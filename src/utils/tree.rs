@@ -1,6 +1,11 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
-use crate::parser::{consts::DependencyKind, types::DependencyTree};
+use crate::parser::consts::{DependencyKind, DiagnosticKind, ModuleFormat, SymbolKind};
+use crate::parser::types::{
+    Dependency, DependencyTree, Diagnostic, ImportSymbol, ModuleTree, SymbolNode, SymbolRef,
+    SymbolTree,
+};
 
 use crate::node_resolve::node_builtins::BUILTINS;
 
@@ -16,14 +21,32 @@ where
     }
 }
 
-pub fn parse_circular(tree: &mut DependencyTree, skip_dynamic_imports: bool) -> Vec<Vec<String>> {
+/// 循环依赖检测时忽略哪些依赖
+pub struct CircularOptions {
+    pub skip_dynamic_imports: bool,
+    pub include_test_mocks: bool, // jest.mock 等默认不参与检测
+    pub eager_only: bool,         // 只检测模块求值时就会形成的循环，见 `--circular-mode eager`
+}
+
+impl CircularOptions {
+    /// 循环依赖检测是否沿着这条依赖继续查找
+    pub fn follows(&self, dep: &Dependency) -> bool {
+        dep.ignored.is_none()
+            && !dep.kind.is_worker()
+            && (self.include_test_mocks || !dep.kind.is_test_mock())
+            && (!self.eager_only || (dep.eager && !dep.type_only))
+            && (!self.skip_dynamic_imports || !dep.is_dynamic())
+    }
+}
+
+pub fn parse_circular(tree: &mut DependencyTree, options: &CircularOptions) -> Vec<Vec<String>> {
     let mut circulars: Vec<Vec<String>> = Vec::new();
 
     fn visit(
         id: String,
         mut used: Vec<String>,
         tree: &mut DependencyTree,
-        skip_dynamic_imports: bool,
+        options: &CircularOptions,
         circulars: &mut Vec<Vec<String>>,
     ) {
         if let Some(index) = used.iter().position(|x| x == &id) {
@@ -32,11 +55,9 @@ pub fn parse_circular(tree: &mut DependencyTree, skip_dynamic_imports: bool) ->
             used.push(id.clone());
 
             if let Some(deps) = deps.as_ref() {
-                for dep in deps {
-                    if !skip_dynamic_imports || dep.kind != DependencyKind::DynamicImport {
-                        if let Some(id) = dep.id.as_deref() {
-                            visit(id.to_string(), used.clone(), tree, skip_dynamic_imports, circulars);
-                        }
+                for dep in deps.iter().filter(|dep| options.follows(dep)) {
+                    if let Some(id) = dep.id.as_deref() {
+                        visit(id.to_string(), used.clone(), tree, options, circulars);
                     }
                 }
             }
@@ -44,18 +65,37 @@ pub fn parse_circular(tree: &mut DependencyTree, skip_dynamic_imports: bool) ->
     }
 
     for id in tree.clone().keys() {
-        visit(
-            id.clone(),
-            Vec::new(),
-            tree,
-            skip_dynamic_imports,
-            &mut circulars,
-        );
+        visit(id.clone(), Vec::new(), tree, options, &mut circulars);
     }
 
     circulars
 }
 
+/// 移除 type-only 的依赖，并丢弃从入口出发不再可达的模块
+pub fn strip_type_only(tree: &DependencyTree, entries: &[String]) -> DependencyTree {
+    let mut output: DependencyTree = HashMap::new();
+    let mut stack: Vec<String> = entries.to_vec();
+
+    while let Some(id) = stack.pop() {
+        if output.contains_key(&id) {
+            continue;
+        }
+        let Some(deps) = tree.get(&id) else {
+            continue;
+        };
+        let deps: Option<Vec<Dependency>> = deps
+            .as_ref()
+            .as_ref()
+            .map(|deps| deps.iter().filter(|dep| !dep.type_only).cloned().collect());
+        if let Some(deps) = &deps {
+            stack.extend(deps.iter().filter_map(|dep| dep.id.clone()));
+        }
+        output.insert(id, Arc::new(deps));
+    }
+
+    output
+}
+
 fn dependents(tree: &DependencyTree, key: &str) -> Vec<String> {
     let mut output: Vec<String> = Vec::new();
     for (k, deps) in tree {
@@ -73,7 +113,44 @@ fn dependents(tree: &DependencyTree, key: &str) -> Vec<String> {
     output
 }
 
-pub fn parse_warnings(tree: &DependencyTree) -> Vec<String> {
+/// 混用模块格式的依赖：ESM 中调用 `require()`，或 CommonJS 同步加载 ESM
+fn format_warning(dep: &Dependency, modules: &ModuleTree) -> Option<String> {
+    let format = |id: &str| modules.get(id).and_then(|node| node.format.as_ref());
+    match format(&dep.issuer)? {
+        ModuleFormat::Esm if dep.kind == DependencyKind::CommonJS => Some(format!(
+            "require \"{}\" in ESM \"{}\"",
+            dep.request,
+            dep.issuer_location()
+        )),
+        ModuleFormat::CommonJS
+            if dep.eager
+                && !dep.type_only
+                && matches!(
+                    dep.kind,
+                    DependencyKind::CommonJS
+                        | DependencyKind::CreateRequire
+                        | DependencyKind::StaticImport
+                        | DependencyKind::StaticExport
+                        | DependencyKind::ImportEquals
+                        | DependencyKind::ExportEquals
+                )
+                && format(dep.id.as_deref()?) == Some(&ModuleFormat::Esm) =>
+        {
+            Some(format!(
+                "require ESM \"{}\" in CommonJS \"{}\"",
+                dep.request,
+                dep.issuer_location()
+            ))
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_warnings(
+    tree: &DependencyTree,
+    modules: &ModuleTree,
+    diagnostics: &[Diagnostic],
+) -> Vec<String> {
     let mut warnings: Vec<String> = Vec::new();
     let mut builtin: HashSet<String> = HashSet::new();
 
@@ -89,13 +166,53 @@ pub fn parse_warnings(tree: &DependencyTree) -> Vec<String> {
             ));
         } else {
             for dep in deps.as_ref().clone().unwrap() {
-                if dep.id.is_none() {
-                    warnings.push(format!("miss \"{}\" in \"{}\"", dep.request, dep.issuer));
+                if dep.ignored.is_some() {
+                    continue;
+                }
+                if dep.is_vite_ignored() && (dep.is_unanalyzable() || dep.id.is_none()) {
+                    continue;
+                }
+                if dep.is_unanalyzable() {
+                    warnings.push(format!(
+                        "unanalyzable import \"{}\" in \"{}\"",
+                        dep.request,
+                        dep.issuer_location()
+                    ));
+                } else if dep.id.is_none() && dep.is_optional() {
+                    warnings.push(format!(
+                        "miss optional \"{}\" in \"{}\"",
+                        dep.request,
+                        dep.issuer_location()
+                    ));
+                } else if dep.id.is_none() {
+                    warnings.push(format!(
+                        "miss \"{}\" in \"{}\"",
+                        dep.request,
+                        dep.issuer_location()
+                    ));
+                }
+                if let Some(warning) = format_warning(&dep, modules) {
+                    warnings.push(warning);
                 }
             }
         }
     }
 
+    for diagnostic in diagnostics {
+        let label = match diagnostic.kind {
+            DiagnosticKind::Read => "read error",
+            DiagnosticKind::Decode => "decode error",
+            DiagnosticKind::Parse => "parse error",
+        };
+        for message in &diagnostic.messages {
+            let file = match &message.loc {
+                Some(loc) => format!("{}:{}", diagnostic.file, loc),
+                None => diagnostic.file.clone(),
+            };
+            warnings.push(format!("{} in \"{}\": {}", label, file, message.message));
+        }
+    }
+
     if !builtin.is_empty() {
         warnings.push(format!(
             "node {}",
@@ -106,3 +223,238 @@ pub fn parse_warnings(tree: &DependencyTree) -> Vec<String> {
     warnings.sort();
     warnings
 }
+
+/// 在 `file` 中查找名为 `name` 的导出，沿 re-export 和 `export *` 找到声明它的文件
+fn resolve_export(
+    tree: &SymbolTree,
+    file: &str,
+    name: &str,
+    visited: &mut HashSet<(String, String)>,
+) -> Option<SymbolRef> {
+    if !visited.insert((file.to_string(), name.to_string())) {
+        return None;
+    }
+    let node = tree.get(file)?.as_ref().as_ref()?;
+
+    if let Some(export) = node.exports.iter().find(|export| export.exported == name) {
+        // import { a } from './a'; export { a }
+        let import = node.imports.iter().find(|import| {
+            export.reexport_id.is_none() && import.local == export.local && import.imported != "*"
+        });
+        return match (&export.reexport_id, import) {
+            // export { a as b } from './a'
+            (Some(source), _) if export.local != "*" => {
+                resolve_export(tree, source, &export.local, visited)
+            }
+            (
+                None,
+                Some(ImportSymbol {
+                    source_id: Some(source),
+                    imported,
+                    ..
+                }),
+            ) => resolve_export(tree, source, imported, visited),
+            // 本地声明，或 export * as ns from './a' 这样的命名空间
+            _ => Some(SymbolRef {
+                file: file.to_string(),
+                exported: name.to_string(),
+            }),
+        };
+    }
+
+    // export * from './a' 不包含 default，`module.exports = require('./a')` 导出整个模块
+    node.exports
+        .iter()
+        .filter(|export| export.local == "*" && export.kind != SymbolKind::Namespace)
+        .filter(|export| name != "default" || export.exported != "*")
+        .filter_map(|export| export.reexport_id.as_deref())
+        .find_map(|source| resolve_export(tree, source, name, visited))
+}
+
+/// 把每个 import 链接到它最终绑定的导出，命名空间导入没有单个对应的导出
+pub fn link_symbols(tree: &SymbolTree) -> SymbolTree {
+    tree.iter()
+        .map(|(file, node)| {
+            let node = node.as_ref().as_ref().map(|node| SymbolNode {
+                imports: node
+                    .imports
+                    .iter()
+                    .map(|import| ImportSymbol {
+                        origin: match (&import.source_id, import.imported.as_str()) {
+                            (Some(source), imported) if imported != "*" => {
+                                resolve_export(tree, source, imported, &mut HashSet::new())
+                            }
+                            _ => None,
+                        },
+                        ..import.clone()
+                    })
+                    .collect(),
+                exports: node.exports.clone(),
+            });
+            (file.clone(), Arc::new(node))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::types::ExportSymbol;
+
+    fn export(local: &str, exported: &str, reexport_id: Option<&str>) -> ExportSymbol {
+        ExportSymbol {
+            local: local.to_string(),
+            exported: exported.to_string(),
+            reexport_source: reexport_id.map(|id| format!("./{}", id)),
+            reexport_id: reexport_id.map(|id| id.to_string()),
+            depends_on: vec![],
+            kind: SymbolKind::Value,
+        }
+    }
+
+    fn import(imported: &str, source_id: &str) -> ImportSymbol {
+        ImportSymbol {
+            id: 0,
+            local: imported.to_string(),
+            imported: imported.to_string(),
+            source: format!("./{}", source_id),
+            type_only: false,
+            source_id: Some(source_id.to_string()),
+            origin: None,
+        }
+    }
+
+    fn node(imports: Vec<ImportSymbol>, exports: Vec<ExportSymbol>) -> Arc<Option<SymbolNode>> {
+        Arc::new(Some(SymbolNode { imports, exports }))
+    }
+
+    #[test]
+    fn test_link_symbols() {
+        let tree: SymbolTree = HashMap::from([
+            (
+                "main.ts".to_string(),
+                node(
+                    vec![
+                        import("a", "index.ts"),
+                        import("b", "index.ts"),
+                        import("default", "index.ts"),
+                        import("missing", "index.ts"),
+                        import("d", "barrel.ts"),
+                    ],
+                    vec![],
+                ),
+            ),
+            (
+                "index.ts".to_string(),
+                node(
+                    vec![],
+                    vec![
+                        export("*", "*", Some("a.ts")),
+                        export("c", "b", Some("b.ts")),
+                        export("*", "*", Some("index.ts")),
+                    ],
+                ),
+            ),
+            (
+                "a.ts".to_string(),
+                node(
+                    vec![],
+                    vec![export("a", "a", None), export("default", "default", None)],
+                ),
+            ),
+            (
+                "b.ts".to_string(),
+                node(vec![], vec![export("c", "c", None)]),
+            ),
+            (
+                "barrel.ts".to_string(),
+                node(vec![import("c", "b.ts")], vec![export("c", "d", None)]),
+            ),
+        ]);
+
+        let linked = link_symbols(&tree);
+        let origins: Vec<Option<SymbolRef>> = linked["main.ts"]
+            .as_ref()
+            .as_ref()
+            .unwrap()
+            .imports
+            .iter()
+            .map(|import| import.origin.clone())
+            .collect();
+        assert_eq!(
+            origins,
+            vec![
+                Some(SymbolRef {
+                    file: "a.ts".to_string(),
+                    exported: "a".to_string()
+                }),
+                Some(SymbolRef {
+                    file: "b.ts".to_string(),
+                    exported: "c".to_string()
+                }),
+                None,
+                None,
+                Some(SymbolRef {
+                    file: "b.ts".to_string(),
+                    exported: "c".to_string()
+                }),
+            ]
+        );
+    }
+
+    fn dependency(kind: DependencyKind, eager: bool, type_only: bool) -> Dependency {
+        Dependency {
+            issuer: "a.ts".to_string(),
+            request: "./b".to_string(),
+            kind,
+            id: Some("b.ts".to_string()),
+            expression: None,
+            context_kind: None,
+            type_only,
+            in_try: false,
+            conditional: false,
+            in_function: !eager,
+            eager,
+            ignored: None,
+            metadata: Default::default(),
+            loc: None,
+        }
+    }
+
+    #[test]
+    fn test_circular_options_eager_only() {
+        let options = CircularOptions {
+            skip_dynamic_imports: false,
+            include_test_mocks: false,
+            eager_only: true,
+        };
+        assert!(options.follows(&dependency(DependencyKind::CommonJS, true, false)));
+        assert!(!options.follows(&dependency(DependencyKind::CommonJS, false, false)));
+        assert!(!options.follows(&dependency(DependencyKind::StaticImport, true, true)));
+        assert!(!options.follows(&dependency(DependencyKind::DynamicImport, false, false)));
+
+        let options = CircularOptions {
+            eager_only: false,
+            ..options
+        };
+        assert!(options.follows(&dependency(DependencyKind::CommonJS, false, false)));
+        assert!(options.follows(&dependency(DependencyKind::StaticImport, true, true)));
+    }
+
+    #[test]
+    fn test_circular_options_test_mocks() {
+        let options = CircularOptions {
+            skip_dynamic_imports: false,
+            include_test_mocks: false,
+            eager_only: false,
+        };
+        assert!(!options.follows(&dependency(DependencyKind::TestMock, true, false)));
+        assert!(options.follows(&dependency(DependencyKind::StaticImport, true, false)));
+
+        let options = CircularOptions {
+            include_test_mocks: true,
+            ..options
+        };
+        assert!(options.follows(&dependency(DependencyKind::TestMock, true, false)));
+    }
+}